@@ -2,13 +2,28 @@
 
 /* std use */
 use std::io::Read as _;
+use std::io::Write as _;
 
 /* crate use */
 
 /* project use */
+use crate::correct;
 use crate::error;
-
-#[derive(std::clone::Clone, std::fmt::Debug, clap::ValueEnum)]
+use crate::filter;
+use crate::report;
+use crate::trim;
+
+#[derive(
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
 pub enum CorrectionMethod {
     One,
     Two,
@@ -17,30 +32,253 @@ pub enum CorrectionMethod {
     GapSize,
 }
 
+impl CorrectionMethod {
+    /// Get configuration of correction method
+    pub fn config(&self, confirm: u8, max_search: u8) -> correct::MethodConfig {
+        correct::Method::from(*self).config(confirm, max_search)
+    }
+}
+
+impl From<CorrectionMethod> for correct::Method {
+    fn from(method: CorrectionMethod) -> Self {
+        match method {
+            CorrectionMethod::One => correct::Method::One,
+            CorrectionMethod::Two => correct::Method::Two,
+            CorrectionMethod::Graph => correct::Method::Graph,
+            CorrectionMethod::Greedy => correct::Method::Greedy,
+            CorrectionMethod::GapSize => correct::Method::GapSize,
+        }
+    }
+}
+
+/// Kmer size assign to a correction method, write 'method=k' on command line
+#[derive(
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    std::cmp::PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub struct MethodKmerSize {
+    /// Correction method
+    pub method: CorrectionMethod,
+
+    /// Kmer size of solid set use by method
+    pub kmer_size: u8,
+}
+
+impl From<MethodKmerSize> for correct::MethodKmerSize {
+    fn from(assign: MethodKmerSize) -> Self {
+        correct::MethodKmerSize {
+            method: assign.method.into(),
+            kmer_size: assign.kmer_size,
+        }
+    }
+}
+
+fn method_kmer_size(value: &str) -> std::result::Result<MethodKmerSize, String> {
+    let (method, kmer_size) = value
+        .split_once('=')
+        .ok_or(format!("'{}' isn't in format method=kmer_size", value))?;
+
+    let kmer_size: u8 = kmer_size.parse().map_err(|e| format!("{}", e))?;
+    if kmer_size == 0 {
+        return Err("kmer size must be greater than 0".to_string());
+    }
+
+    Ok(MethodKmerSize {
+        method: <CorrectionMethod as clap::ValueEnum>::from_str(method, true)?,
+        kmer_size,
+    })
+}
+
+/// Parameters of a correction method, write 'method:key=value,key=value' on command line
+#[derive(
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    std::cmp::PartialEq,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub struct MethodParams {
+    /// Correction method
+    pub method: CorrectionMethod,
+
+    /// Number of kmer required to validate correction
+    pub confirm: Option<u8>,
+
+    /// Number of base use to try correct error
+    pub max_search: Option<u8>,
+}
+
+impl MethodParams {
+    /// Override parameters of configuration by parameters set
+    pub fn apply(&self, config: correct::MethodConfig) -> correct::MethodConfig {
+        match config {
+            correct::MethodConfig::One { confirm } => correct::MethodConfig::One {
+                confirm: self.confirm.unwrap_or(confirm),
+            },
+            correct::MethodConfig::Two { confirm } => correct::MethodConfig::Two {
+                confirm: self.confirm.unwrap_or(confirm),
+            },
+            correct::MethodConfig::Graph => correct::MethodConfig::Graph,
+            correct::MethodConfig::Greedy {
+                max_search,
+                confirm,
+            } => correct::MethodConfig::Greedy {
+                max_search: self.max_search.unwrap_or(max_search),
+                confirm: self.confirm.unwrap_or(confirm),
+            },
+            correct::MethodConfig::GapSize { confirm } => correct::MethodConfig::GapSize {
+                confirm: self.confirm.unwrap_or(confirm),
+            },
+        }
+    }
+}
+
+fn method_params(value: &str) -> std::result::Result<MethodParams, String> {
+    let (method, params) = value
+        .split_once(':')
+        .ok_or(format!("'{}' isn't in format method:key=value", value))?;
+
+    let mut result = MethodParams {
+        method: <CorrectionMethod as clap::ValueEnum>::from_str(method, true)?,
+        confirm: None,
+        max_search: None,
+    };
+
+    for param in params.split(',') {
+        let (key, value) = param
+            .split_once('=')
+            .ok_or(format!("'{}' isn't in format key=value", param))?;
+        let value = Some(value.parse().map_err(|e| format!("{}", e))?);
+
+        match (key.replace('-', "_").as_str(), result.method) {
+            ("confirm", CorrectionMethod::Graph) => {
+                return Err("graph method didn't have confirm parameter".to_string())
+            }
+            ("confirm", _) => result.confirm = value,
+            ("max_search", CorrectionMethod::Greedy) => result.max_search = value,
+            ("max_search", _) => {
+                return Err("only greedy method have max_search parameter".to_string())
+            }
+            (other, _) => return Err(format!("unknown parameter '{}'", other)),
+        }
+    }
+
+    Ok(result)
+}
+
+#[derive(
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReportFormat {
+    Tsv,
+    Jsonl,
+}
+
+impl From<ReportFormat> for report::Format {
+    fn from(format: ReportFormat) -> Self {
+        match format {
+            ReportFormat::Tsv => report::Format::Tsv,
+            ReportFormat::Jsonl => report::Format::Jsonl,
+        }
+    }
+}
+
+/// Policy apply on non ACGT bases
+#[derive(
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    clap::ValueEnum,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum AmbiguousPolicy {
+    /// Correct each run of A, C, G, T independently, ambiguous bases are keep
+    Split,
+    /// Kmer with ambiguous base is never solid, correctors try to replace it
+    Resolve,
+}
+
+impl From<AmbiguousPolicy> for correct::AmbiguousPolicy {
+    fn from(policy: AmbiguousPolicy) -> Self {
+        match policy {
+            AmbiguousPolicy::Split => correct::AmbiguousPolicy::Split,
+            AmbiguousPolicy::Resolve => correct::AmbiguousPolicy::Resolve,
+        }
+    }
+}
+
 /// Brutal Rewrite, a kmer read corrector
-#[derive(clap::Parser, std::fmt::Debug)]
+#[derive(
+    clap::Parser, std::fmt::Debug, std::clone::Clone, serde::Serialize, serde::Deserialize,
+)]
 #[clap(
     version = "0.1",
     author = "Pierre Marijon <pierre.marijon@hhu.de>",
     about = "Br: Brutal rewrite a simple long read corrector based on kmer spectrum methode"
 )]
+#[serde(rename_all = "kebab-case")]
 pub struct Command {
+    /// Path to a toml configuration file, value set on command line override value of file
+    #[clap(short = 'F', long = "config")]
+    #[serde(skip)]
+    config: Option<std::path::PathBuf>,
+
+    /// Path to write effective configuration in toml, not write by default
+    #[clap(short = 'D', long = "dump-config")]
+    #[serde(skip)]
+    dump_config: Option<std::path::PathBuf>,
+
     /// Path to inputs, default read stdin
     #[clap(short = 'i', long = "inputs")]
     inputs: Option<Vec<std::path::PathBuf>>,
 
-    /// Path to output, default read stdout
+    /// Path to output, one per input, default write stdout, a single value with {name}, {stem} or {dir} is a template apply on each input
     #[clap(short = 'o', long = "outputs")]
     outputs: Option<Vec<std::path::PathBuf>>,
 
-    /// Correct in two side
-    #[clap(short = 's', long = "two-side")]
-    two_side: bool,
+    /// Directory where outputs are write, output name is input name if outputs isn't set
+    #[clap(short = 'd', long = "output-dir")]
+    output_dir: Option<std::path::PathBuf>,
+
+    /// Compression of outputs, default is deduce from output extension, stdout isn't compressed
+    #[clap(short = 'z', long = "compress")]
+    compress: Option<Compression>,
+
+    /// Compression level between 1 and 9, default value '6'
+    #[clap(short = 'l', long = "compression-level", value_parser = clap::value_parser!(u8).range(1..=9))]
+    compression_level: Option<u8>,
+
+    /// Correct in two side, --two-side=false disable it if configuration file set it
+    #[clap(short = 's', long = "two-side", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    two_side: Option<bool>,
 
     /// Correction method
     #[clap(short = 'c', long = "corrections")]
     corrections: Option<Vec<CorrectionMethod>>,
 
+    /// Kmer size of solid set use by a correction method (e.g. gap-size=13), method without assignment use the first solid set
+    #[clap(short = 'm', long = "method-kmer-sizes", value_delimiter = ',', value_parser = method_kmer_size)]
+    method_kmer_sizes: Option<Vec<MethodKmerSize>>,
+
+    /// Parameters of a correction method (e.g. greedy:max_search=12,confirm=4), override confirm and max-search for this method
+    #[clap(short = 'P', long = "method-params", value_parser = method_params)]
+    method_params: Option<Vec<MethodParams>>,
+
     /// Number of kmer required to validate correction
     #[clap(short = 'C', long = "confirm")]
     confirm: Option<u8>,
@@ -49,33 +287,177 @@ pub struct Command {
     #[clap(short = 'M', long = "max-search")]
     max_search: Option<u8>,
 
+    /// Phred quality assign to inserted base in fastq output, default value '20'
+    #[clap(short = 'Q', long = "insertion-quality")]
+    insertion_quality: Option<u8>,
+
+    /// Policy apply on non ACGT bases, default value 'resolve'
+    #[clap(short = 'n', long = "ambiguous")]
+    ambiguous: Option<AmbiguousPolicy>,
+
+    /// Trim read ends not covered by solid kmer, read without solid kmer are drop
+    #[clap(short = 'x', long = "trim", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    trim: Option<bool>,
+
+    /// Split read at internal weak region longer than this value, imply trim, no split by default
+    #[clap(short = 'g', long = "split")]
+    split: Option<usize>,
+
+    /// Add ratio of solid kmer before and after correction in read description
+    #[clap(long = "annotate-ratio", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    annotate_ratio: Option<bool>,
+
+    /// Drop read with a ratio of solid kmer after correction lower than this value, no filter by default
+    #[clap(long = "min-solid-ratio")]
+    min_solid_ratio: Option<f64>,
+
+    /// Path to correction report, no report by default
+    #[clap(short = 'r', long = "report")]
+    report: Option<std::path::PathBuf>,
+
+    /// Format of correction report, default value 'tsv'
+    #[clap(short = 'R', long = "report-format")]
+    report_format: Option<ReportFormat>,
+
+    /// Path to write correction statistics in json, a summary is always write on stderr unless quiet is set
+    #[clap(short = 'S', long = "stats")]
+    stats: Option<std::path::PathBuf>,
+
+    /// Log and skip invalid records, by default first invalid record stop br
+    #[clap(short = 'I', long = "skip-invalid", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    skip_invalid: Option<bool>,
+
     /// Number of sequence record load in buffer, default 8192
     #[clap(short = 'b', long = "record_buffer")]
     pub record_buffer: Option<u64>,
 
-    /// SubCommand
+    /// SubCommand, subcommand on command line replace subcommand of configuration file
     #[clap(subcommand)]
-    pub subcommand: SubCommand,
+    subcommand: Option<SubCommand>,
 
     #[cfg(feature = "parallel")]
     /// Number of theard use 0 use all avaible core, default value 0
     #[clap(short = 't', long = "threads")]
     threads: Option<usize>,
 
+    #[cfg(feature = "parallel")]
+    /// Number of inputs with records in correction at same time, default value 1
+    #[clap(short = 'j', long = "concurrent-files")]
+    concurrent_files: Option<usize>,
+
     /// Silence all output
-    #[clap(short = 'q', long = "quiet")]
-    quiet: bool,
+    #[clap(short = 'q', long = "quiet", num_args = 0..=1, require_equals = true, default_missing_value = "true")]
+    quiet: Option<bool>,
 
     /// Verbose mode (-v, -vv, -vvv, etc)
     #[clap(short = 'v', long = "verbosity", action = clap::ArgAction::Count)]
+    #[serde(default)]
     verbosity: u8,
 
     /// Timestamp (sec, ms, ns, none)
     #[clap(short = 'T', long = "timestamp")]
+    #[serde(skip)]
     ts: Option<stderrlog::Timestamp>,
 }
 
 impl Command {
+    /// Fill value not set on command line with value of configuration file, if it's set
+    pub fn load_config(self) -> error::Result<Self> {
+        match &self.config {
+            None => Ok(self),
+            Some(path) => {
+                let file: Command = toml::from_str(&std::fs::read_to_string(path)?)?;
+
+                Ok(self.merge(file))
+            }
+        }
+    }
+
+    /// Fill value not set in self with value of other, subcommand of self replace subcommand of other, boolean options explicitly set in self override other even if they are false
+    pub fn merge(self, other: Command) -> Self {
+        Self {
+            config: self.config,
+            dump_config: self.dump_config,
+            inputs: self.inputs.or(other.inputs),
+            outputs: self.outputs.or(other.outputs),
+            output_dir: self.output_dir.or(other.output_dir),
+            compress: self.compress.or(other.compress),
+            compression_level: self.compression_level.or(other.compression_level),
+            two_side: self.two_side.or(other.two_side),
+            skip_invalid: self.skip_invalid.or(other.skip_invalid),
+            corrections: self.corrections.or(other.corrections),
+            method_kmer_sizes: self.method_kmer_sizes.or(other.method_kmer_sizes),
+            method_params: self.method_params.or(other.method_params),
+            confirm: self.confirm.or(other.confirm),
+            max_search: self.max_search.or(other.max_search),
+            insertion_quality: self.insertion_quality.or(other.insertion_quality),
+            ambiguous: self.ambiguous.or(other.ambiguous),
+            trim: self.trim.or(other.trim),
+            split: self.split.or(other.split),
+            annotate_ratio: self.annotate_ratio.or(other.annotate_ratio),
+            min_solid_ratio: self.min_solid_ratio.or(other.min_solid_ratio),
+            report: self.report.or(other.report),
+            report_format: self.report_format.or(other.report_format),
+            stats: self.stats.or(other.stats),
+            record_buffer: self.record_buffer.or(other.record_buffer),
+            subcommand: self.subcommand.or(other.subcommand),
+            #[cfg(feature = "parallel")]
+            threads: self.threads.or(other.threads),
+            #[cfg(feature = "parallel")]
+            concurrent_files: self.concurrent_files.or(other.concurrent_files),
+            quiet: self.quiet.or(other.quiet),
+            verbosity: if self.verbosity != 0 {
+                self.verbosity
+            } else {
+                other.verbosity
+            },
+            ts: self.ts.or(other.ts),
+        }
+    }
+
+    /// Write effective configuration in toml, default value of unset parameters are write, if output is set
+    pub fn dump_config(&self) -> error::Result<()> {
+        if let Some(path) = &self.dump_config {
+            let mut writer = create(path)?;
+            writer.write_all(toml::to_string(&self.resolved())?.as_bytes())?;
+            writer.flush()?;
+        }
+
+        Ok(())
+    }
+
+    /// Get a copy of configuration where unset parameters with a default value are set
+    fn resolved(&self) -> Self {
+        Self {
+            compression_level: Some(self.compression_level()),
+            two_side: Some(self.two_side()),
+            corrections: Some(self.corrections()),
+            confirm: Some(self.confirm()),
+            max_search: Some(self.max_search()),
+            insertion_quality: Some(self.insertion_quality()),
+            ambiguous: Some(self.ambiguous.unwrap_or(AmbiguousPolicy::Resolve)),
+            trim: Some(self.trim.unwrap_or(false)),
+            annotate_ratio: Some(self.annotate_ratio.unwrap_or(false)),
+            report_format: Some(self.report_format()),
+            skip_invalid: Some(self.skip_invalid()),
+            record_buffer: Some(self.record_buffer()),
+            subcommand: self.subcommand.as_ref().map(SubCommand::resolved),
+            #[cfg(feature = "parallel")]
+            threads: Some(self.threads()),
+            #[cfg(feature = "parallel")]
+            concurrent_files: Some(self.concurrent_files()),
+            quiet: Some(self.quiet()),
+            ..self.clone()
+        }
+    }
+
+    /// Get subcommand
+    pub fn subcommand(&self) -> error::Result<&SubCommand> {
+        self.subcommand
+            .as_ref()
+            .ok_or(error::Error::MissingSubCommand.into())
+    }
+
     /// Get inputs
     pub fn inputs(&self) -> error::Result<Vec<Box<dyn std::io::BufRead>>> {
         let mut inputs: Vec<Box<dyn std::io::BufRead>> = vec![];
@@ -92,19 +474,28 @@ impl Command {
         }
     }
 
-    /// Get output
-    pub fn outputs(&self) -> error::Result<Vec<Box<dyn std::io::Write>>> {
-        let mut outputs: Vec<Box<dyn std::io::Write>> = vec![];
+    /// Get output, each output must be finish after correction
+    pub fn outputs(&self) -> error::Result<Vec<Output<'static>>> {
+        let mut outputs = vec![];
 
-        match &self.outputs {
-            None => {
-                if outputs.is_empty() {
-                    outputs.push(Box::new(std::io::BufWriter::new(std::io::stdout())))
-                }
-            }
+        match self.output_paths()? {
+            None => outputs.push(compress(
+                Box::new(std::io::stdout()),
+                self.compress.unwrap_or(Compression::None),
+                self.compression_level(),
+            )?),
             Some(paths) => {
                 for path in paths {
-                    outputs.push(create(path)?);
+                    let compression = self
+                        .compress
+                        .or_else(|| Compression::from_path(&path))
+                        .unwrap_or(Compression::None);
+
+                    outputs.push(compress(
+                        Box::new(std::fs::File::create(path)?),
+                        compression,
+                        self.compression_level(),
+                    )?);
                 }
             }
         }
@@ -112,9 +503,69 @@ impl Command {
         Ok(outputs)
     }
 
+    /// Get compression level
+    pub fn compression_level(&self) -> u8 {
+        self.compression_level.unwrap_or(6).clamp(1, 9)
+    }
+
+    /// Get path of outputs, None if output is stdout, number of outputs is checked against number of inputs
+    pub fn output_paths(&self) -> error::Result<Option<Vec<std::path::PathBuf>>> {
+        let inputs = self.inputs.as_deref().unwrap_or(&[]);
+        let outputs = self.outputs.as_deref().unwrap_or(&[]);
+
+        let template = match outputs {
+            [output] if is_template(output) => Some(output.to_string_lossy().to_string()),
+            [] if self.output_dir.is_some() => Some("{name}".to_string()),
+            _ => None,
+        };
+
+        let paths: Vec<std::path::PathBuf> = match template {
+            Some(_) if inputs.is_empty() => {
+                return Err(error::Error::OutputTemplateRequireInputs.into())
+            }
+            Some(template) => inputs
+                .iter()
+                .map(|input| output_from_template(&template, input))
+                .collect(),
+            None if outputs.is_empty() && inputs.len() <= 1 => return Ok(None),
+            None if outputs.is_empty() => {
+                return Err(error::Error::InputOutputMismatch {
+                    inputs: inputs.len(),
+                    outputs: 1,
+                }
+                .into())
+            }
+            None => outputs.to_vec(),
+        };
+
+        if paths.len() != inputs.len().max(1) {
+            return Err(error::Error::InputOutputMismatch {
+                inputs: inputs.len().max(1),
+                outputs: paths.len(),
+            }
+            .into());
+        }
+
+        let paths: Vec<std::path::PathBuf> = match &self.output_dir {
+            Some(dir) => paths.iter().map(|path| dir.join(path)).collect(),
+            None => paths,
+        };
+
+        for (index, path) in paths.iter().enumerate() {
+            if paths[..index].iter().any(|other| same_path(path, other)) {
+                return Err(error::Error::DuplicateOutput(path.clone()).into());
+            }
+            if inputs.iter().any(|input| same_path(path, input)) {
+                return Err(error::Error::OutputIsInput(path.clone()).into());
+            }
+        }
+
+        Ok(Some(paths))
+    }
+
     /// Get two_side
     pub fn two_side(&self) -> bool {
-        self.two_side
+        self.two_side.unwrap_or(false)
     }
 
     /// Get correction method
@@ -131,6 +582,61 @@ impl Command {
         }
     }
 
+    /// Get configuration of each correction method
+    pub fn method_configs(&self) -> Vec<correct::MethodConfig> {
+        self.corrections()
+            .into_iter()
+            .map(|method| {
+                self.method_params
+                    .iter()
+                    .flatten()
+                    .filter(|params| params.method == method)
+                    .fold(
+                        method.config(self.confirm(), self.max_search()),
+                        |config, params| params.apply(config),
+                    )
+            })
+            .collect()
+    }
+
+    /// Get trimming parameter, None if trimming isn't request
+    pub fn trim(&self) -> Option<trim::Trim> {
+        if self.trim.unwrap_or(false) || self.split.is_some() {
+            Some(trim::Trim::new(self.split))
+        } else {
+            None
+        }
+    }
+
+    /// Get solid ratio parameter, None if annotation and filter aren't request
+    pub fn solid_ratio(&self) -> Option<filter::SolidRatio> {
+        let annotate_ratio = self.annotate_ratio.unwrap_or(false);
+
+        if annotate_ratio || self.min_solid_ratio.is_some() {
+            Some(filter::SolidRatio::new(
+                annotate_ratio,
+                self.min_solid_ratio,
+            ))
+        } else {
+            None
+        }
+    }
+
+    /// Get kmer size assign to correction method, kmer size is round down to odd value like solid set kmer size
+    pub fn method_kmer_sizes(&self) -> Vec<correct::MethodKmerSize> {
+        self.method_kmer_sizes
+            .iter()
+            .flatten()
+            .map(|assign| MethodKmerSize {
+                kmer_size: assign
+                    .kmer_size
+                    .saturating_sub(!(assign.kmer_size & 0b1) & 0b1),
+                ..*assign
+            })
+            .map(|assign| assign.into())
+            .collect()
+    }
+
     /// Get confirm
     pub fn confirm(&self) -> u8 {
         self.confirm.unwrap_or(5)
@@ -141,6 +647,56 @@ impl Command {
         self.max_search.unwrap_or(7)
     }
 
+    /// Get insertion quality
+    pub fn insertion_quality(&self) -> u8 {
+        self.insertion_quality.unwrap_or(20)
+    }
+
+    /// Get ambiguous base policy
+    pub fn ambiguous(&self) -> correct::AmbiguousPolicy {
+        self.ambiguous.unwrap_or(AmbiguousPolicy::Resolve).into()
+    }
+
+    /// Get correction report
+    pub fn report(&self) -> error::Result<Option<report::Report>> {
+        match &self.report {
+            None => Ok(None),
+            Some(path) => Ok(Some(report::Report::new(
+                create(path)?,
+                self.report_format().into(),
+            )?)),
+        }
+    }
+
+    /// Get report format
+    pub fn report_format(&self) -> ReportFormat {
+        self.report_format.unwrap_or(ReportFormat::Tsv)
+    }
+
+    /// Get correction statistics output
+    pub fn stats(&self) -> error::Result<Option<Box<dyn std::io::Write + std::marker::Send>>> {
+        match &self.stats {
+            None => Ok(None),
+            Some(path) => Ok(Some(create(path)?)),
+        }
+    }
+
+    /// Get skip invalid
+    pub fn skip_invalid(&self) -> bool {
+        self.skip_invalid.unwrap_or(false)
+    }
+
+    /// Get name of inputs, use in parsing error
+    pub fn input_names(&self) -> Vec<String> {
+        match &self.inputs {
+            None => vec!["stdin".to_string()],
+            Some(paths) => paths
+                .iter()
+                .map(|path| path.to_string_lossy().to_string())
+                .collect(),
+        }
+    }
+
     /// Get record buffer
     pub fn record_buffer(&self) -> u64 {
         self.record_buffer.unwrap_or(8192)
@@ -152,6 +708,12 @@ impl Command {
         self.threads.unwrap_or(0)
     }
 
+    /// Get number of inputs corrected at same time
+    #[cfg(feature = "parallel")]
+    pub fn concurrent_files(&self) -> usize {
+        self.concurrent_files.unwrap_or(1)
+    }
+
     /// Get verbosity level
     pub fn verbosity(&self) -> usize {
         self.verbosity as usize
@@ -159,7 +721,7 @@ impl Command {
 
     /// Get quiet
     pub fn quiet(&self) -> bool {
-        self.quiet
+        self.quiet.unwrap_or(false)
     }
 
     /// Get timestamp granularity
@@ -169,7 +731,10 @@ impl Command {
 }
 
 /// Enumeration of subcommand
-#[derive(clap::Subcommand, std::fmt::Debug)]
+#[derive(
+    clap::Subcommand, std::fmt::Debug, std::clone::Clone, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
 pub enum SubCommand {
     /// With Count
     Count(Count),
@@ -184,8 +749,27 @@ pub enum SubCommand {
     LargeKmer(LargeKmer),
 }
 
+impl SubCommand {
+    /// Get a copy of subcommand where unset parameters with a default value are set
+    fn resolved(&self) -> Self {
+        match self {
+            SubCommand::Count(count) => SubCommand::Count(Count {
+                save_format: Some(count.save_format()),
+                ..count.clone()
+            }),
+            SubCommand::Fasta(fasta) => SubCommand::Fasta(Fasta {
+                rounds: Some(fasta.rounds()),
+                save_format: Some(fasta.save_format()),
+                ..fasta.clone()
+            }),
+            other => other.clone(),
+        }
+    }
+}
+
 /// SubCommand Count
-#[derive(clap::Args, std::fmt::Debug)]
+#[derive(clap::Args, std::fmt::Debug, std::clone::Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Count {
     /// Path to fasta kmer inputs
     #[clap(short = 'i', long = "inputs")]
@@ -195,6 +779,18 @@ pub struct Count {
     #[clap(short = 'a', long = "abundance")]
     abundance: Option<pcon::CountTypeNoAtomic>,
 
+    /// Path to save solid kmer set, solid kmer set isn't saved by default
+    #[clap(short = 's', long = "save-solid")]
+    save_solid: Option<std::path::PathBuf>,
+
+    /// Format of saved solid kmer set, default value 'solid'
+    #[clap(short = 'f', long = "save-format")]
+    save_format: Option<SolidOutput>,
+
+    /// Path to write kmer abundance spectrum and selected threshold in csv, not write by default
+    #[clap(short = 'p', long = "spectrum")]
+    spectrum: Option<std::path::PathBuf>,
+
     /// Abundance selection method
     #[clap(subcommand)]
     abundance_selection: Option<AbundanceSelection>,
@@ -222,10 +818,101 @@ impl Count {
     pub fn abundance_selection(&self) -> Option<AbundanceSelection> {
         self.abundance_selection
     }
+
+    /// Get output of solid kmer set
+    pub fn save_solid(&self) -> error::Result<Option<Box<dyn std::io::Write + std::marker::Send>>> {
+        match &self.save_solid {
+            None => Ok(None),
+            Some(path) => Ok(Some(create(path)?)),
+        }
+    }
+
+    /// Get format of saved solid kmer set
+    pub fn save_format(&self) -> SolidOutput {
+        self.save_format.unwrap_or(SolidOutput::Solid)
+    }
+
+    /// Get output of kmer abundance spectrum
+    pub fn spectrum(&self) -> error::Result<Option<Box<dyn std::io::Write + std::marker::Send>>> {
+        match &self.spectrum {
+            None => Ok(None),
+            Some(path) => Ok(Some(create(path)?)),
+        }
+    }
+}
+
+/// Compression format of outputs
+#[derive(
+    clap::ValueEnum,
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum Compression {
+    /// Gzip compression
+    Gz,
+    /// Bzip2 compression
+    Bz2,
+    /// Xz compression
+    Xz,
+    /// Zstandard compression
+    Zstd,
+    /// No compression
+    None,
+}
+
+impl Compression {
+    /// Deduce compression from path extension, None if extension isn't a compression extension
+    pub fn from_path<P>(path: P) -> Option<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        match path.as_ref().extension()?.to_str()? {
+            "gz" => Some(Compression::Gz),
+            "bz2" => Some(Compression::Bz2),
+            "xz" => Some(Compression::Xz),
+            "zst" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+}
+
+/// Format of saved solid kmer set
+#[derive(
+    clap::ValueEnum,
+    std::clone::Clone,
+    std::marker::Copy,
+    std::fmt::Debug,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
+pub enum SolidOutput {
+    /// Pcon solid format, can be reload with solid subcommand
+    Solid,
+    /// Fasta with one record per canonical solid kmer
+    Fasta,
+    /// Sorted canonical solid kmers, can be reload with solid or large-kmer subcommand and memory mapped
+    Mmap,
+    /// Uncompressed pcon solid format, file size is 2^(2k-4) bytes so only for small k, can be reload with solid subcommand and memory mapped
+    MmapBitfield,
 }
 
 /// Enumeration of abundance selection
-#[derive(clap::Subcommand, std::fmt::Debug, std::clone::Clone, std::marker::Copy)]
+#[derive(
+    clap::Subcommand,
+    std::fmt::Debug,
+    std::clone::Clone,
+    std::marker::Copy,
+    serde::Serialize,
+    serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
 pub enum AbundanceSelection {
     /// First Minimum
     FirstMinimum,
@@ -240,21 +927,59 @@ pub enum AbundanceSelection {
     PercentLeast { percent: f64 },
 }
 
+impl AbundanceSelection {
+    /// Get a human readable name of method
+    pub fn name(&self) -> String {
+        match self {
+            AbundanceSelection::FirstMinimum => "first-minimum".to_string(),
+            AbundanceSelection::Rarefaction { percent } => format!("rarefaction {}", percent),
+            AbundanceSelection::PercentMost { percent } => format!("percent-most {}", percent),
+            AbundanceSelection::PercentLeast { percent } => format!("percent-least {}", percent),
+        }
+    }
+}
+
 /// SubCommand Fasta
-#[derive(clap::Args, std::fmt::Debug)]
+#[derive(clap::Args, std::fmt::Debug, std::clone::Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Fasta {
     /// Path to fasta kmer inputs
     #[clap(short = 'i', long = "inputs")]
+    #[serde(default)]
     inputs: Vec<std::path::PathBuf>,
 
     /// Size of kmer
-    #[clap(short = 'k', long = "kmer-size")]
+    #[clap(short = 'k', long = "kmer-size", value_parser = clap::value_parser!(u8).range(1..))]
     kmer_size: u8,
 
+    /// Number of correction round, kmer are recount from corrected reads between rounds, default value '1'
+    #[clap(short = 'r', long = "rounds")]
+    rounds: Option<usize>,
+
+    /// Kmer size of each round, last value is reuse by next rounds, default value is kmer-size
+    #[clap(short = 'K', long = "round-kmer-sizes", value_delimiter = ',', value_parser = clap::value_parser!(u8).range(1..))]
+    round_kmer_sizes: Option<Vec<u8>>,
+
+    /// Size of kmer of additional solid set build from same inputs, correction method use them with method-kmer-sizes
+    #[clap(short = 'e', long = "extra-kmer-sizes", value_delimiter = ',', value_parser = clap::value_parser!(u8).range(1..))]
+    extra_kmer_sizes: Option<Vec<u8>>,
+
     /// Minimal abundance, default value 0
     #[clap(short = 'a', long = "abundance")]
     abundance: Option<pcon::CountTypeNoAtomic>,
 
+    /// Path to save solid kmer set, solid kmer set isn't saved by default
+    #[clap(short = 's', long = "save-solid")]
+    save_solid: Option<std::path::PathBuf>,
+
+    /// Format of saved solid kmer set, default value 'solid'
+    #[clap(short = 'f', long = "save-format")]
+    save_format: Option<SolidOutput>,
+
+    /// Path to write kmer abundance spectrum and selected threshold in csv, not write by default
+    #[clap(short = 'p', long = "spectrum")]
+    spectrum: Option<std::path::PathBuf>,
+
     /// Abundance selection method
     #[clap(subcommand)]
     pub abundance_selection: Option<AbundanceSelection>,
@@ -275,7 +1000,32 @@ impl Fasta {
 
     /// Get size of kmer
     pub fn kmer_size(&self) -> u8 {
-        self.kmer_size - (!(self.kmer_size & 0b1) & 0b1)
+        self.kmer_size.saturating_sub(!(self.kmer_size & 0b1) & 0b1)
+    }
+
+    /// Get number of correction round
+    pub fn rounds(&self) -> usize {
+        self.rounds.unwrap_or(1).max(1)
+    }
+
+    /// Get size of kmer of additional solid set
+    pub fn extra_kmer_sizes(&self) -> Vec<u8> {
+        self.extra_kmer_sizes
+            .iter()
+            .flatten()
+            .map(|k| k.saturating_sub(!(k & 0b1) & 0b1))
+            .collect()
+    }
+
+    /// Get size of kmer use in a round
+    pub fn round_kmer_size(&self, round: usize) -> u8 {
+        match &self.round_kmer_sizes {
+            Some(sizes) if !sizes.is_empty() => {
+                let k = sizes[round.min(sizes.len() - 1)];
+                k.saturating_sub(!(k & 0b1) & 0b1)
+            }
+            _ => self.kmer_size(),
+        }
     }
 
     /// Get abundance
@@ -287,11 +1037,37 @@ impl Fasta {
     pub fn abundance_selection(&self) -> Option<AbundanceSelection> {
         self.abundance_selection
     }
+
+    /// Get output of solid kmer set
+    pub fn save_solid(&self) -> error::Result<Option<Box<dyn std::io::Write + std::marker::Send>>> {
+        match &self.save_solid {
+            None => Ok(None),
+            Some(path) => Ok(Some(create(path)?)),
+        }
+    }
+
+    /// Get format of saved solid kmer set
+    pub fn save_format(&self) -> SolidOutput {
+        self.save_format.unwrap_or(SolidOutput::Solid)
+    }
+
+    /// Get output of kmer abundance spectrum
+    pub fn spectrum(&self) -> error::Result<Option<Box<dyn std::io::Write + std::marker::Send>>> {
+        match &self.spectrum {
+            None => Ok(None),
+            Some(path) => Ok(Some(create(path)?)),
+        }
+    }
 }
 
-#[derive(clap::ValueEnum, std::clone::Clone, std::fmt::Debug)]
+#[derive(
+    clap::ValueEnum, std::clone::Clone, std::fmt::Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
 pub enum SolidInput {
     Solid,
+    Mmap,
+    MmapBitfield,
     #[cfg(feature = "csv")]
     Csv,
     Fasta,
@@ -302,25 +1078,34 @@ pub enum SolidInput {
 }
 
 /// SubCommand Solid
-#[derive(clap::Args, std::fmt::Debug)]
+#[derive(clap::Args, std::fmt::Debug, std::clone::Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct Solid {
-    /// Path to input
-    #[clap(short = 'i', long = "input")]
-    input: std::path::PathBuf,
+    /// Path to inputs, each input is a solid set, correction method use them with method-kmer-sizes
+    #[clap(short = 'i', long = "inputs", required = true)]
+    inputs: Vec<std::path::PathBuf>,
 
     /// Input type
     #[clap(short = 'f', long = "format")]
     format: SolidInput,
 
-    /// Size of kmer
-    #[clap(short = 'k', long = "kmer-size")]
-    kmer_size: Option<u8>,
+    /// Size of kmer of each input, last value is reuse by next inputs, required by csv, fasta and fastq format, checked against kff and mmap header
+    #[clap(short = 'k', long = "kmer-sizes", value_delimiter = ',', value_parser = clap::value_parser!(u8).range(1..))]
+    kmer_sizes: Option<Vec<u8>>,
 }
 
 impl Solid {
-    /// Get input
-    pub fn input(&self) -> error::Result<Box<dyn std::io::BufRead + std::marker::Send>> {
-        open_send(&self.input)
+    /// Get input of a solid set
+    pub fn input(
+        &self,
+        index: usize,
+    ) -> error::Result<Box<dyn std::io::BufRead + std::marker::Send>> {
+        open_send(&self.inputs[index])
+    }
+
+    /// Get path of inputs
+    pub fn inputs(&self) -> &[std::path::PathBuf] {
+        &self.inputs
     }
 
     /// Get format
@@ -328,14 +1113,21 @@ impl Solid {
         self.format.clone()
     }
 
-    /// Get size of kmer
-    pub fn kmer_size(&self) -> Option<u8> {
-        self.kmer_size
+    /// Get size of kmer of a solid set
+    pub fn kmer_size(&self, index: usize) -> Option<u8> {
+        self.kmer_sizes
+            .as_ref()
+            .and_then(|sizes| sizes.get(index).or(sizes.last()))
+            .copied()
     }
 }
 
-#[derive(clap::ValueEnum, std::clone::Clone, std::fmt::Debug)]
+#[derive(
+    clap::ValueEnum, std::clone::Clone, std::fmt::Debug, serde::Serialize, serde::Deserialize,
+)]
+#[serde(rename_all = "kebab-case")]
 pub enum LargeKmerInput {
+    Mmap,
     #[cfg(feature = "csv")]
     Csv,
     Fasta,
@@ -346,33 +1138,38 @@ pub enum LargeKmerInput {
 }
 
 /// SubCommand LargeKmer
-#[derive(clap::Args, std::fmt::Debug)]
+#[derive(clap::Args, std::fmt::Debug, std::clone::Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub struct LargeKmer {
-    /// Path to input
-    #[clap(short = 'i', long = "input")]
-    input: std::path::PathBuf,
+    /// Path to inputs, each input is a solid set, correction method use them with method-kmer-sizes
+    #[clap(short = 'i', long = "inputs", required = true)]
+    inputs: Vec<std::path::PathBuf>,
 
     /// Input type
     #[clap(short = 'f', long = "format")]
     format: LargeKmerInput,
 
-    /// Size of kmer
-    #[clap(short = 'k', long = "kmer-size")]
-    kmer_size: u8,
+    /// Size of kmer of each input, last value is reuse by next inputs
+    #[clap(short = 'k', long = "kmer-sizes", value_delimiter = ',', required = true, value_parser = clap::value_parser!(u8).range(1..))]
+    kmer_sizes: Vec<u8>,
+
+    /// Path to save solid kmer set of each input in mmap format, one path per input, mmap inputs aren't saved again
+    #[clap(short = 's', long = "save-solid", value_delimiter = ',')]
+    save_solid: Option<Vec<std::path::PathBuf>>,
 }
 
 impl LargeKmer {
-    /// Get input
-    pub fn input(&self) -> error::Result<Box<dyn std::io::BufRead + std::marker::Send>> {
-        match self.format {
-            #[cfg(feature = "csv")]
-            LargeKmerInput::Csv => open_send(&self.input),
-            LargeKmerInput::Fasta => open_send(&self.input),
-            #[cfg(feature = "fastq")]
-            LargeKmerInput::Fastq => open_send(&self.input),
-            #[cfg(feature = "kff")]
-            LargeKmerInput::Kff => open_send(&self.input),
-        }
+    /// Get input of a solid set
+    pub fn input(
+        &self,
+        index: usize,
+    ) -> error::Result<Box<dyn std::io::BufRead + std::marker::Send>> {
+        open_send(&self.inputs[index])
+    }
+
+    /// Get path of inputs
+    pub fn inputs(&self) -> &[std::path::PathBuf] {
+        &self.inputs
     }
 
     /// Get format
@@ -380,9 +1177,87 @@ impl LargeKmer {
         self.format.clone()
     }
 
-    /// Get size of kmer
-    pub fn kmer_size(&self) -> u8 {
-        self.kmer_size
+    /// Get size of kmer of a solid set
+    pub fn kmer_size(&self, index: usize) -> u8 {
+        self.kmer_sizes
+            .get(index)
+            .or(self.kmer_sizes.last())
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Get output of solid kmer set of an input in mmap format
+    pub fn save_solid(
+        &self,
+        index: usize,
+    ) -> error::Result<Option<Box<dyn std::io::Write + std::marker::Send>>> {
+        match self.save_solid.as_ref().and_then(|paths| paths.get(index)) {
+            None => Ok(None),
+            Some(path) => Ok(Some(create(path)?)),
+        }
+    }
+
+    /// Get largest kmer size of solid sets
+    pub fn max_kmer_size(&self) -> u8 {
+        (0..self.inputs.len())
+            .map(|index| self.kmer_size(index))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Return true if path contains a template placeholder
+fn is_template(path: &std::path::Path) -> bool {
+    let path = path.to_string_lossy();
+
+    ["{name}", "{stem}", "{dir}"]
+        .iter()
+        .any(|placeholder| path.contains(placeholder))
+}
+
+/// Sequence and compression extensions remove from input file name to build {stem}
+const KNOWN_EXTENSIONS: &[&str] = &[
+    "fa", "fasta", "fna", "fq", "fastq", "gz", "bz2", "xz", "zst",
+];
+
+/// Build output path of an input, {name} is replace by input file name, {stem} by file name without sequence and compression extensions and {dir} by input directory
+fn output_from_template(template: &str, input: &std::path::Path) -> std::path::PathBuf {
+    let name = input
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut stem = name.as_str();
+    while let Some((head, extension)) = stem.rsplit_once('.') {
+        if head.is_empty() || !KNOWN_EXTENSIONS.contains(&extension.to_lowercase().as_str()) {
+            break;
+        }
+        stem = head;
+    }
+    let dir = match input.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_string_lossy().to_string(),
+        _ => ".".to_string(),
+    };
+
+    std::path::PathBuf::from(
+        template
+            .replace("{name}", &name)
+            .replace("{stem}", stem)
+            .replace("{dir}", &dir),
+    )
+}
+
+/// Return true if two paths point to same file, paths are compared without canonicalization if one didn't exist
+fn same_path(a: &std::path::Path, b: &std::path::Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => {
+            let components = |path: &'_ std::path::Path| {
+                path.components()
+                    .filter(|component| component != &std::path::Component::CurDir)
+                    .collect::<Vec<_>>()
+            };
+            components(a) == components(b)
+        }
     }
 }
 
@@ -397,6 +1272,101 @@ where
     Ok(boxed)
 }
 
+/// Wrap writer in a compression writer, compression level is clamp between 1 and 9
+fn compress<'a>(
+    writer: Box<dyn std::io::Write + std::marker::Send + 'a>,
+    compression: Compression,
+    level: u8,
+) -> error::Result<Output<'a>> {
+    let level = level.clamp(1, 9);
+
+    let encoder = match compression {
+        Compression::Gz => Encoder::Gz(flate2::write::GzEncoder::new(
+            writer,
+            flate2::Compression::new(level as u32),
+        )),
+        Compression::Bz2 => Encoder::Bz2(bzip2::write::BzEncoder::new(
+            writer,
+            bzip2::Compression::new(level as u32),
+        )),
+        Compression::Xz => Encoder::Xz(xz2::write::XzEncoder::new(writer, level as u32)),
+        Compression::Zstd => {
+            Encoder::Zstd(zstd::stream::write::Encoder::new(writer, level as i32)?)
+        }
+        Compression::None => Encoder::None(writer),
+    };
+
+    Ok(Output {
+        writer: std::io::BufWriter::new(encoder),
+    })
+}
+
+/// Output of corrected reads, compressed stream must be finish to write its end
+pub struct Output<'a> {
+    writer: std::io::BufWriter<Encoder<'a>>,
+}
+
+impl Output<'_> {
+    /// Flush buffer, finish compressed stream and flush underlying writer
+    pub fn finish(self) -> std::io::Result<()> {
+        let mut writer = match self.writer.into_inner().map_err(|e| e.into_error())? {
+            Encoder::Gz(encoder) => encoder.finish()?,
+            Encoder::Bz2(encoder) => encoder.finish()?,
+            Encoder::Xz(encoder) => encoder.finish()?,
+            Encoder::Zstd(encoder) => encoder.finish()?,
+            Encoder::None(writer) => writer,
+        };
+
+        writer.flush()
+    }
+}
+
+impl std::io::Write for Output<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+/// Compression encoder of an output
+enum Encoder<'a> {
+    /// Gzip encoder
+    Gz(flate2::write::GzEncoder<Box<dyn std::io::Write + std::marker::Send + 'a>>),
+    /// Bzip2 encoder
+    Bz2(bzip2::write::BzEncoder<Box<dyn std::io::Write + std::marker::Send + 'a>>),
+    /// Xz encoder
+    Xz(xz2::write::XzEncoder<Box<dyn std::io::Write + std::marker::Send + 'a>>),
+    /// Zstandard encoder
+    Zstd(zstd::stream::write::Encoder<'static, Box<dyn std::io::Write + std::marker::Send + 'a>>),
+    /// Uncompressed writer
+    None(Box<dyn std::io::Write + std::marker::Send + 'a>),
+}
+
+impl std::io::Write for Encoder<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Encoder::Gz(encoder) => encoder.write(buf),
+            Encoder::Bz2(encoder) => encoder.write(buf),
+            Encoder::Xz(encoder) => encoder.write(buf),
+            Encoder::Zstd(encoder) => encoder.write(buf),
+            Encoder::None(writer) => writer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Encoder::Gz(encoder) => encoder.flush(),
+            Encoder::Bz2(encoder) => encoder.flush(),
+            Encoder::Xz(encoder) => encoder.flush(),
+            Encoder::Zstd(encoder) => encoder.flush(),
+            Encoder::None(writer) => writer.flush(),
+        }
+    }
+}
+
 fn open<P>(path: P) -> error::Result<Box<dyn std::io::BufRead>>
 where
     P: std::convert::AsRef<std::path::Path>,
@@ -432,33 +1402,79 @@ mod tests {
         let subcmd = Fasta {
             inputs: vec![],
             kmer_size: 14,
+            rounds: None,
+            round_kmer_sizes: Some(vec![17, 22]),
+            extra_kmer_sizes: Some(vec![25, 12]),
             abundance: Some(2),
+            save_solid: None,
+            save_format: None,
+            spectrum: None,
             abundance_selection: None,
         };
 
         let cmd = Command {
+            config: None,
+            dump_config: None,
             inputs: None,
             outputs: None,
-            two_side: true,
+            output_dir: None,
+            compress: None,
+            compression_level: None,
+            two_side: Some(true),
             confirm: Some(5),
             max_search: Some(7),
+            insertion_quality: None,
+            ambiguous: None,
+            trim: None,
+            split: None,
+            annotate_ratio: None,
+            min_solid_ratio: None,
+            report: None,
+            report_format: None,
+            stats: None,
+            skip_invalid: None,
             record_buffer: Some(8192),
             corrections: None,
+            method_kmer_sizes: None,
+            method_params: None,
             verbosity: 3,
-            quiet: false,
+            quiet: None,
             ts: None,
-            subcommand: SubCommand::Fasta(subcmd),
+            subcommand: Some(SubCommand::Fasta(subcmd)),
         };
 
         assert_eq!(cmd.verbosity(), 3);
         assert!(!cmd.quiet());
         assert!(matches!(cmd.timestamp(), stderrlog::Timestamp::Off));
+        assert_eq!(cmd.insertion_quality(), 20);
+        assert_eq!(cmd.ambiguous(), correct::AmbiguousPolicy::Resolve);
+        assert!(matches!(cmd.report_format(), ReportFormat::Tsv));
+        assert!(cmd.report().unwrap().is_none());
+        assert!(cmd.stats().unwrap().is_none());
+        assert!(cmd.method_kmer_sizes().is_empty());
+        assert_eq!(
+            cmd.method_configs()[3],
+            correct::MethodConfig::Greedy {
+                max_search: 7,
+                confirm: cmd.confirm()
+            }
+        );
+        assert!(cmd.trim().is_none());
+        assert!(cmd.solid_ratio().is_none());
 
         match cmd.subcommand {
-            SubCommand::Fasta(subcmd) => {
+            Some(SubCommand::Fasta(subcmd)) => {
                 assert_eq!(subcmd.kmer_size(), 13);
+                assert_eq!(subcmd.rounds(), 1);
+                assert_eq!(subcmd.round_kmer_size(0), 17);
+                assert_eq!(subcmd.round_kmer_size(1), 21);
+                assert_eq!(subcmd.round_kmer_size(5), 21);
+                assert_eq!(subcmd.extra_kmer_sizes(), vec![25, 11]);
                 assert_eq!(subcmd.abundance(), Some(2));
                 assert!(matches!(subcmd.abundance_selection(), None));
+                assert!(subcmd.save_solid().unwrap().is_none());
+                assert!(matches!(subcmd.save_format(), SolidOutput::Solid));
+                assert!(subcmd.spectrum().unwrap().is_none());
             }
             _ => unreachable!(),
         }
@@ -470,37 +1486,341 @@ mod tests {
         let subcmd = Fasta {
             inputs: vec![],
             kmer_size: 14,
+            rounds: None,
+            round_kmer_sizes: Some(vec![17, 22]),
+            extra_kmer_sizes: Some(vec![25, 12]),
             abundance: Some(2),
+            save_solid: None,
+            save_format: None,
+            spectrum: None,
             abundance_selection: None,
         };
 
         let cmd = Command {
+            config: None,
+            dump_config: None,
             inputs: None,
             outputs: None,
-            two_side: true,
+            output_dir: None,
+            compress: None,
+            compression_level: None,
+            two_side: Some(true),
             confirm: Some(5),
             max_search: Some(7),
+            insertion_quality: None,
+            ambiguous: None,
+            trim: None,
+            split: None,
+            annotate_ratio: None,
+            min_solid_ratio: None,
+            report: None,
+            report_format: None,
+            stats: None,
+            skip_invalid: None,
             record_buffer: Some(8192),
             corrections: None,
+            method_kmer_sizes: None,
+            method_params: None,
             verbosity: 3,
-            quiet: false,
+            quiet: None,
             ts: None,
             threads: Some(8),
-            subcommand: SubCommand::Fasta(subcmd),
+            concurrent_files: Some(4),
+            subcommand: Some(SubCommand::Fasta(subcmd)),
         };
 
         assert_eq!(cmd.verbosity(), 3);
         assert!(!cmd.quiet());
         assert!(matches!(cmd.timestamp(), stderrlog::Timestamp::Off));
+        assert_eq!(cmd.insertion_quality(), 20);
+        assert_eq!(cmd.ambiguous(), correct::AmbiguousPolicy::Resolve);
+        assert!(matches!(cmd.report_format(), ReportFormat::Tsv));
+        assert!(cmd.report().unwrap().is_none());
+        assert!(cmd.stats().unwrap().is_none());
         assert_eq!(cmd.threads(), 8);
+        assert_eq!(cmd.concurrent_files(), 4);
 
         match cmd.subcommand {
-            SubCommand::Fasta(subcmd) => {
+            Some(SubCommand::Fasta(subcmd)) => {
                 assert_eq!(subcmd.kmer_size(), 13);
+                assert_eq!(subcmd.rounds(), 1);
+                assert_eq!(subcmd.round_kmer_size(0), 17);
+                assert_eq!(subcmd.round_kmer_size(1), 21);
+                assert_eq!(subcmd.round_kmer_size(5), 21);
+                assert_eq!(subcmd.extra_kmer_sizes(), vec![25, 11]);
                 assert_eq!(subcmd.abundance(), Some(2));
                 assert!(matches!(subcmd.abundance_selection(), None));
+                assert!(subcmd.save_solid().unwrap().is_none());
+                assert!(matches!(subcmd.save_format(), SolidOutput::Solid));
+                assert!(subcmd.spectrum().unwrap().is_none());
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn method_kmer() {
+        assert_eq!(
+            method_kmer_size("gap-size=13").unwrap(),
+            MethodKmerSize {
+                method: CorrectionMethod::GapSize,
+                kmer_size: 13
+            }
+        );
+        assert_eq!(
+            correct::MethodKmerSize::from(method_kmer_size("greedy=15").unwrap()),
+            correct::MethodKmerSize {
+                method: correct::Method::Greedy,
+                kmer_size: 15
+            }
+        );
+        assert!(method_kmer_size("greedy").is_err());
+        assert!(method_kmer_size("greedy=a").is_err());
+        assert!(method_kmer_size("other=13").is_err());
+        assert!(method_kmer_size("greedy=0").is_err());
+
+        let cmd = <Command as clap::Parser>::try_parse_from(["br", "-m", "greedy=12,gap-size=13"])
+            .unwrap();
+        assert_eq!(
+            cmd.method_kmer_sizes(),
+            vec![
+                correct::MethodKmerSize {
+                    method: correct::Method::Greedy,
+                    kmer_size: 11
+                },
+                correct::MethodKmerSize {
+                    method: correct::Method::GapSize,
+                    kmer_size: 13
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn null_kmer_size() {
+        let parse = |args: &[&str]| <Command as clap::Parser>::try_parse_from(args);
+
+        assert!(parse(&["br", "fasta", "-k", "0"]).is_err());
+        assert!(parse(&["br", "fasta", "-k", "11", "-K", "13,0"]).is_err());
+        assert!(parse(&["br", "solid", "-i", "a.fa", "-f", "fasta", "-k", "0"]).is_err());
+        assert!(parse(&["br", "large-kmer", "-i", "a.fa", "-f", "fasta", "-k", "0"]).is_err());
+        assert!(parse(&["br", "large-kmer", "-i", "a.fa", "-f", "fasta", "-k", "1"]).is_ok());
+    }
+
+    #[test]
+    fn method_parameters() {
+        let params = method_params("greedy:max_search=12,confirm=4").unwrap();
+        assert_eq!(
+            params,
+            MethodParams {
+                method: CorrectionMethod::Greedy,
+                confirm: Some(4),
+                max_search: Some(12),
+            }
+        );
+        assert_eq!(
+            params.apply(CorrectionMethod::Greedy.config(5, 7)),
+            correct::MethodConfig::Greedy {
+                max_search: 12,
+                confirm: 4
+            }
+        );
+
+        assert_eq!(
+            method_params("one:confirm=3")
+                .unwrap()
+                .apply(CorrectionMethod::One.config(5, 7)),
+            correct::MethodConfig::One { confirm: 3 }
+        );
+
+        assert!(method_params("one").is_err());
+        assert!(method_params("one:max_search=3").is_err());
+        assert!(method_params("graph:confirm=3").is_err());
+        assert!(method_params("two:other=3").is_err());
+        assert!(method_params("two:confirm=a").is_err());
+    }
+
+    #[test]
+    fn config() {
+        let file: Command = toml::from_str(
+            r#"
+inputs = ["reads.fasta"]
+confirm = 4
+max-search = 9
+corrections = ["one", "greedy"]
+method-params = [{ method = "greedy", confirm = 6 }]
+
+[subcommand.fasta]
+inputs = ["reads.fasta"]
+kmer-size = 17
+
+[subcommand.fasta.abundance-selection.rarefaction]
+percent = 0.5
+"#,
+        )
+        .unwrap();
+
+        let cmd = <Command as clap::Parser>::try_parse_from(["br", "-C", "3"])
+            .unwrap()
+            .merge(file);
+
+        assert_eq!(cmd.confirm(), 3);
+        assert_eq!(cmd.max_search(), 9);
+        assert_eq!(
+            cmd.method_configs(),
+            vec![
+                correct::MethodConfig::One { confirm: 3 },
+                correct::MethodConfig::Greedy {
+                    max_search: 9,
+                    confirm: 6
+                },
+            ]
+        );
+        match cmd.subcommand().unwrap() {
+            SubCommand::Fasta(subcmd) => {
+                assert_eq!(subcmd.kmer_size(), 17);
+                assert!(matches!(
+                    subcmd.abundance_selection(),
+                    Some(AbundanceSelection::Rarefaction { percent }) if percent == 0.5
+                ));
             }
             _ => unreachable!(),
         }
+
+        let reload: Command = toml::from_str(&toml::to_string(&cmd).unwrap()).unwrap();
+        assert_eq!(reload.confirm(), 3);
+        assert_eq!(reload.method_configs(), cmd.method_configs());
+        assert!(reload.subcommand().is_ok());
+
+        let cmd = <Command as clap::Parser>::try_parse_from(["br"]).unwrap();
+        assert!(cmd.subcommand().is_err());
+    }
+
+    #[test]
+    fn config_flags() {
+        let file = || -> Command {
+            toml::from_str(
+                r#"
+two-side = true
+trim = true
+skip-invalid = true
+"#,
+            )
+            .unwrap()
+        };
+        let parse = |args: &[&str]| <Command as clap::Parser>::try_parse_from(args).unwrap();
+
+        let cmd = parse(&["br"]).merge(file());
+        assert!(cmd.two_side());
+        assert!(cmd.trim().is_some());
+        assert!(cmd.skip_invalid());
+        assert!(cmd.solid_ratio().is_none());
+
+        let cmd = parse(&["br", "--two-side=false", "-x=false", "--annotate-ratio"]).merge(file());
+        assert!(!cmd.two_side());
+        assert!(cmd.trim().is_none());
+        assert!(cmd.skip_invalid());
+        assert!(cmd.solid_ratio().is_some());
+
+        let cmd = parse(&["br", "-s", "-I", "fasta", "-k", "11"]);
+        assert!(cmd.two_side());
+        assert!(cmd.skip_invalid());
+        assert!(cmd.subcommand().is_ok());
+    }
+
+    #[test]
+    fn output_paths() {
+        let parse = |args: &[&str]| <Command as clap::Parser>::try_parse_from(args).unwrap();
+        let paths = |args: &[&str]| parse(args).output_paths();
+
+        assert_eq!(paths(&["br"]).unwrap(), None);
+        assert_eq!(paths(&["br", "-i", "a.fa"]).unwrap(), None);
+        assert_eq!(
+            paths(&["br", "-i", "a.fa", "-i", "b.fa", "-o", "c.fa", "-o", "d.fa"]).unwrap(),
+            Some(vec!["c.fa".into(), "d.fa".into()])
+        );
+        assert_eq!(
+            paths(&[
+                "br",
+                "-i",
+                "data/a.fa",
+                "-i",
+                "b.fq.gz",
+                "-o",
+                "{dir}/{stem}.corrected.fa.gz"
+            ])
+            .unwrap(),
+            Some(vec![
+                "data/a.corrected.fa.gz".into(),
+                "./b.corrected.fa.gz".into()
+            ])
+        );
+        assert_eq!(
+            paths(&["br", "-i", "data/a.fa", "-i", "b.fa", "-d", "out"]).unwrap(),
+            Some(vec!["out/a.fa".into(), "out/b.fa".into()])
+        );
+        assert_eq!(
+            paths(&["br", "-i", "a.fa", "-d", "out", "-o", "{stem}.fa"]).unwrap(),
+            Some(vec!["out/a.fa".into()])
+        );
+
+        assert!(paths(&["br", "-i", "a.fa", "-i", "b.fa", "-i", "c.fa", "-o", "d.fa"]).is_err());
+        assert!(paths(&["br", "-i", "a.fa", "-o", "c.fa", "-o", "d.fa"]).is_err());
+        assert!(paths(&["br", "-i", "a.fa", "-i", "b.fa"]).is_err());
+        assert!(paths(&["br", "-o", "{stem}.fa"]).is_err());
+
+        assert_eq!(
+            paths(&[
+                "br",
+                "-i",
+                "sample.R1.fq.gz",
+                "-i",
+                "sample.R2.fastq",
+                "-o",
+                "{stem}.fa"
+            ])
+            .unwrap(),
+            Some(vec!["sample.R1.fa".into(), "sample.R2.fa".into()])
+        );
+        assert!(paths(&["br", "-i", "a/r.fa", "-i", "b/r.fa", "-d", "out"]).is_err());
+        assert!(paths(&["br", "-i", "a.fa", "-i", "b.fa", "-o", "c.fa", "-o", "./c.fa"]).is_err());
+        assert!(paths(&["br", "-i", "a.fa", "-o", "./a.fa"]).is_err());
+        assert!(paths(&["br", "-i", "data/a.fa", "-d", "data"]).is_err());
+        assert!(paths(&["br", "-i", "data/a.fa.gz", "-o", "{dir}/{stem}.fa.gz"]).is_err());
+    }
+
+    #[test]
+    fn compression() {
+        assert_eq!(
+            Compression::from_path("reads.fasta.gz"),
+            Some(Compression::Gz)
+        );
+        assert_eq!(Compression::from_path("reads.bz2"), Some(Compression::Bz2));
+        assert_eq!(Compression::from_path("reads.xz"), Some(Compression::Xz));
+        assert_eq!(Compression::from_path("reads.zst"), Some(Compression::Zstd));
+        assert_eq!(Compression::from_path("reads.fasta"), None);
+        assert_eq!(Compression::from_path("reads"), None);
+
+        for (compression, format) in [
+            (Compression::Gz, niffler::compression::Format::Gzip),
+            (Compression::Bz2, niffler::compression::Format::Bzip),
+            (Compression::Xz, niffler::compression::Format::Lzma),
+            (Compression::Zstd, niffler::compression::Format::Zstd),
+            (Compression::None, niffler::compression::Format::No),
+        ] {
+            let mut buffer = Vec::new();
+            let mut writer =
+                compress(Box::new(std::io::Cursor::new(&mut buffer)), compression, 9).unwrap();
+            writer.write_all(b">1\nACGT\n").unwrap();
+            writer.finish().unwrap();
+
+            let (mut reader, found) =
+                niffler::get_reader(Box::new(std::io::Cursor::new(buffer))).unwrap();
+            assert_eq!(found, format);
+
+            let mut content = Vec::new();
+            reader.read_to_end(&mut content).unwrap();
+            assert_eq!(content, b">1\nACGT\n");
+        }
     }
 }
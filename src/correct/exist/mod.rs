@@ -4,21 +4,33 @@ use strum::IntoEnumIterator;
 
 /* crate use */
 use crate::correct::*;
+use crate::kmer;
 use crate::set;
 
 ///////////////////////////////////////////
 // Generic Trait for correction scenario //
 ///////////////////////////////////////////
-pub trait Scenario: std::fmt::Debug + Copy {
+pub trait Scenario: std::fmt::Debug + Copy + Sync {
+    const METHOD: &'static str;
+
+    fn name(&self) -> String;
+
     fn init(&self, c: usize, k: u8) -> Self;
 
     fn c(&self) -> usize;
 
-    fn apply(&self, valid_kmer: &set::BoxKmerSet, kmer: u64, seq: &[u8]) -> Option<(u64, usize)>;
+    fn apply<K>(&self, valid_kmer: &set::BoxKmerSet<K>, kmer: K, seq: &[u8]) -> Option<(K, usize)>
+    where
+        K: kmer::Kmer;
 
-    fn correct(&self, valid_kmer: &set::BoxKmerSet, kmer: u64, _seq: &[u8]) -> (Vec<u8>, usize);
+    fn correct<K>(&self, valid_kmer: &set::BoxKmerSet<K>, kmer: K, _seq: &[u8]) -> (Vec<u8>, usize)
+    where
+        K: kmer::Kmer;
 
-    fn get_score(&self, valid_kmer: &set::BoxKmerSet, ori: u64, seq: &[u8]) -> usize {
+    fn get_score<K>(&self, valid_kmer: &set::BoxKmerSet<K>, ori: K, seq: &[u8]) -> usize
+    where
+        K: kmer::Kmer,
+    {
         if let Some((mut kmer, offset)) = self.apply(valid_kmer, ori, seq) {
             if !valid_kmer.get(kmer) {
                 return 0;
@@ -46,7 +58,10 @@ pub trait Scenario: std::fmt::Debug + Copy {
         }
     }
 
-    fn one_more(&self, valid_kmer: &set::BoxKmerSet, mut kmer: u64, seq: &[u8]) -> bool {
+    fn one_more<K>(&self, valid_kmer: &set::BoxKmerSet<K>, mut kmer: K, seq: &[u8]) -> bool
+    where
+        K: kmer::Kmer,
+    {
         // Get correction
         let (corr, offset) = self.correct(valid_kmer, kmer, seq);
 
@@ -73,20 +88,22 @@ pub trait Scenario: std::fmt::Debug + Copy {
 //////////////////////////////////////////
 // Exsist use scenario to correct error //
 //////////////////////////////////////////
-pub struct Exist<'a, S>
+pub struct Exist<'a, S, K = u64>
 where
     S: Scenario + IntoEnumIterator,
+    K: kmer::Kmer,
 {
-    valid_kmer: &'a set::BoxKmerSet<'a>,
+    valid_kmer: &'a set::BoxKmerSet<'a, K>,
     c: u8,
     _phantom: std::marker::PhantomData<&'a S>,
 }
 
-impl<'a, S> Exist<'a, S>
+impl<'a, S, K> Exist<'a, S, K>
 where
     S: Scenario + IntoEnumIterator,
+    K: kmer::Kmer,
 {
-    pub fn new(valid_kmer: &'a set::BoxKmerSet, c: u8) -> Self {
+    pub fn new(valid_kmer: &'a set::BoxKmerSet<K>, c: u8) -> Self {
         Self {
             valid_kmer,
             c,
@@ -94,7 +111,7 @@ where
         }
     }
 
-    fn get_scenarii(&self, kmer: u64, seq: &[u8]) -> Vec<S> {
+    fn get_scenarii(&self, kmer: K, seq: &[u8]) -> Vec<S> {
         let mut scenarii: Vec<S> = Vec::new();
 
         for mut scenario in S::iter() {
@@ -107,22 +124,33 @@ where
 
         scenarii
     }
+
+    fn apply_scenario(&self, scenario: &S, kmer: K, seq: &[u8]) -> Correction {
+        let (local_correct, offset) = scenario.correct(self.valid_kmer, kmer, seq);
+
+        Correction::new(local_correct, offset, Some(scenario.name()))
+    }
 }
 
-impl<'a, S> Corrector for Exist<'a, S>
+impl<'a, S, K> Corrector<K> for Exist<'a, S, K>
 where
     S: Scenario + IntoEnumIterator,
+    K: kmer::Kmer,
 {
-    fn valid_kmer(&self) -> &set::BoxKmerSet {
+    fn valid_kmer(&self) -> &set::BoxKmerSet<K> {
         self.valid_kmer
     }
 
-    fn correct_error(&self, kmer: u64, seq: &[u8]) -> Option<(Vec<u8>, usize)> {
+    fn name(&self) -> &'static str {
+        S::METHOD
+    }
+
+    fn correct_error(&self, kmer: K, seq: &[u8]) -> Outcome {
         let alts = alt_nucs(self.valid_kmer, kmer);
 
         if alts.len() != 1 {
             debug!("not one alts {:?}", alts);
-            return None;
+            return Outcome::from_alts(&alts);
         }
         debug!("one alts {:?}", alts);
 
@@ -131,19 +159,19 @@ where
 
         if scenarii.is_empty() {
             debug!("no scenario");
-            None
+            Outcome::NoScenario
         } else if scenarii.len() == 1 {
             debug!("one {:?}", scenarii);
-            Some(scenarii[0].correct(self.valid_kmer, corr, seq))
+            Outcome::Corrected(self.apply_scenario(&scenarii[0], corr, seq))
         } else {
             debug!("multiple {:?}", scenarii);
             scenarii.retain(|x| x.one_more(self.valid_kmer, corr, seq));
             debug!("multiple {:?}", scenarii);
 
             if scenarii.len() == 1 {
-                Some(scenarii[0].correct(self.valid_kmer, corr, seq))
+                Outcome::Corrected(self.apply_scenario(&scenarii[0], corr, seq))
             } else {
-                None
+                Outcome::MultipleScenarios
             }
         }
     }
@@ -21,16 +21,17 @@ SOFTWARE.
  */
 
 /* crate use */
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, IntoStaticStr};
 
 /* crate use */
 use crate::correct::exist::{Exist, Scenario};
+use crate::kmer;
 use crate::set;
 
 ////////////////////////////////////
 // Scenario for correct one error //
 ////////////////////////////////////
-#[derive(Debug, EnumIter, Clone, Copy)]
+#[derive(Debug, EnumIter, IntoStaticStr, Clone, Copy)]
 pub enum ScenarioOne {
     I(usize, u8),
     S(usize, u8),
@@ -38,6 +39,12 @@ pub enum ScenarioOne {
 }
 
 impl Scenario for ScenarioOne {
+    const METHOD: &'static str = "One";
+
+    fn name(&self) -> String {
+        format!("ScenarioOne::{}", <&'static str>::from(self))
+    }
+
     fn init(&self, c: usize, k: u8) -> Self {
         match self {
             ScenarioOne::I(_, _) => ScenarioOne::I(c, k),
@@ -54,7 +61,10 @@ impl Scenario for ScenarioOne {
         }
     }
 
-    fn apply(&self, _valid_kmer: &set::BoxKmerSet, kmer: u64, _seq: &[u8]) -> Option<(u64, usize)> {
+    fn apply<K>(&self, _valid_kmer: &set::BoxKmerSet<K>, kmer: K, _seq: &[u8]) -> Option<(K, usize)>
+    where
+        K: kmer::Kmer,
+    {
         match self {
             ScenarioOne::I(_, _) => Some((kmer, 2)),
             ScenarioOne::S(_, _) => Some((kmer, 1)),
@@ -62,16 +72,19 @@ impl Scenario for ScenarioOne {
         }
     }
 
-    fn correct(&self, _valid_kmer: &set::BoxKmerSet, kmer: u64, _seq: &[u8]) -> (Vec<u8>, usize) {
+    fn correct<K>(&self, _valid_kmer: &set::BoxKmerSet<K>, kmer: K, _seq: &[u8]) -> (Vec<u8>, usize)
+    where
+        K: kmer::Kmer,
+    {
         match self {
-            ScenarioOne::I(_, _) => (vec![cocktail::kmer::bit2nuc(kmer & 0b11)], 2),
-            ScenarioOne::S(_, _) => (vec![cocktail::kmer::bit2nuc(kmer & 0b11)], 1),
-            ScenarioOne::D(_, _) => (vec![cocktail::kmer::bit2nuc(kmer & 0b11)], 0),
+            ScenarioOne::I(_, _) => (vec![cocktail::kmer::bit2nuc(kmer.last_nuc())], 2),
+            ScenarioOne::S(_, _) => (vec![cocktail::kmer::bit2nuc(kmer.last_nuc())], 1),
+            ScenarioOne::D(_, _) => (vec![cocktail::kmer::bit2nuc(kmer.last_nuc())], 0),
         }
     }
 }
 
-pub type One<'a> = Exist<'a, ScenarioOne>;
+pub type One<'a, K = u64> = Exist<'a, ScenarioOne, K>;
 
 #[cfg(test)]
 mod tests {
@@ -21,17 +21,18 @@ SOFTWARE.
  */
 
 /* crate use */
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, IntoStaticStr};
 
 /* crate use */
 use crate::correct::exist::{Exist, Scenario};
 use crate::correct::*;
+use crate::kmer;
 use crate::set;
 
 ////////////////////////////////////
 // Scenario for correct two error //
 ////////////////////////////////////
-#[derive(Debug, EnumIter, Clone, Copy)]
+#[derive(Debug, EnumIter, IntoStaticStr, Clone, Copy)]
 pub enum ScenarioTwo {
     II(usize, u8),
     IS(usize, u8),
@@ -50,6 +51,12 @@ pub enum ScenarioTwo {
 }
 
 impl Scenario for ScenarioTwo {
+    const METHOD: &'static str = "Two";
+
+    fn name(&self) -> String {
+        format!("ScenarioTwo::{}", <&'static str>::from(self))
+    }
+
     fn init(&self, c: usize, k: u8) -> Self {
         match self {
             ScenarioTwo::II(_, _) => ScenarioTwo::II(c, k),
@@ -86,12 +93,15 @@ impl Scenario for ScenarioTwo {
         }
     }
 
-    fn apply(
+    fn apply<K>(
         &self,
-        valid_kmer: &set::BoxKmerSet,
-        mut kmer: u64,
+        valid_kmer: &set::BoxKmerSet<K>,
+        mut kmer: K,
         seq: &[u8],
-    ) -> Option<(u64, usize)> {
+    ) -> Option<(K, usize)>
+    where
+        K: kmer::Kmer,
+    {
         match self {
             ScenarioTwo::II(_, _) => Some((kmer, 3)), // kmer not change check from base 3
             ScenarioTwo::IS(_, _) => Some((kmer, 2)), // kmer not change check from base 2
@@ -255,10 +265,13 @@ impl Scenario for ScenarioTwo {
         }
     }
 
-    fn correct(&self, valid_kmer: &set::BoxKmerSet, kmer: u64, seq: &[u8]) -> (Vec<u8>, usize) {
+    fn correct<K>(&self, valid_kmer: &set::BoxKmerSet<K>, kmer: K, seq: &[u8]) -> (Vec<u8>, usize)
+    where
+        K: kmer::Kmer,
+    {
         match self {
-            ScenarioTwo::II(_, _) => (vec![cocktail::kmer::bit2nuc(kmer & 0b11)], 2),
-            ScenarioTwo::IS(_, _) => (vec![cocktail::kmer::bit2nuc(kmer & 0b11)], 2),
+            ScenarioTwo::II(_, _) => (vec![cocktail::kmer::bit2nuc(kmer.last_nuc())], 2),
+            ScenarioTwo::IS(_, _) => (vec![cocktail::kmer::bit2nuc(kmer.last_nuc())], 2),
             ScenarioTwo::SS(_, _) | ScenarioTwo::SD(_, _) | ScenarioTwo::DD(_, _) => {
                 let (corr, offset) = self
                     .apply(valid_kmer, kmer, seq)
@@ -266,13 +279,13 @@ impl Scenario for ScenarioTwo {
 
                 (
                     vec![
-                        cocktail::kmer::bit2nuc((corr & 0b1100) >> 2),
-                        cocktail::kmer::bit2nuc(corr & 0b11),
+                        cocktail::kmer::bit2nuc((corr >> 2).last_nuc()),
+                        cocktail::kmer::bit2nuc(corr.last_nuc()),
                     ],
                     offset,
                 )
             }
-            ScenarioTwo::ICI(_, _) => (vec![cocktail::kmer::bit2nuc(kmer & 0b11)], 3),
+            ScenarioTwo::ICI(_, _) => (vec![cocktail::kmer::bit2nuc(kmer.last_nuc())], 3),
             ScenarioTwo::ICD(_, _) => {
                 let (corr, offset) = self
                     .apply(valid_kmer, kmer, seq)
@@ -280,8 +293,8 @@ impl Scenario for ScenarioTwo {
 
                 (
                     vec![
-                        cocktail::kmer::bit2nuc((corr & 0b1100) >> 2),
-                        cocktail::kmer::bit2nuc(corr & 0b11),
+                        cocktail::kmer::bit2nuc((corr >> 2).last_nuc()),
+                        cocktail::kmer::bit2nuc(corr.last_nuc()),
                     ],
                     offset - 1,
                 )
@@ -293,8 +306,8 @@ impl Scenario for ScenarioTwo {
 
                 (
                     vec![
-                        cocktail::kmer::bit2nuc((corr & 0b1100) >> 2),
-                        cocktail::kmer::bit2nuc(corr & 0b11),
+                        cocktail::kmer::bit2nuc((corr >> 2).last_nuc()),
+                        cocktail::kmer::bit2nuc(corr.last_nuc()),
                     ],
                     offset + 1,
                 )
@@ -309,9 +322,9 @@ impl Scenario for ScenarioTwo {
 
                 (
                     vec![
-                        cocktail::kmer::bit2nuc((corr & 0b110000) >> 4),
-                        cocktail::kmer::bit2nuc((corr & 0b1100) >> 2),
-                        cocktail::kmer::bit2nuc(corr & 0b11),
+                        cocktail::kmer::bit2nuc((corr >> 4).last_nuc()),
+                        cocktail::kmer::bit2nuc((corr >> 2).last_nuc()),
+                        cocktail::kmer::bit2nuc(corr.last_nuc()),
                     ],
                     offset,
                 )
@@ -325,7 +338,7 @@ impl Scenario for ScenarioTwo {
     }
 }
 
-pub type Two<'a> = Exist<'a, ScenarioTwo>;
+pub type Two<'a, K = u64> = Exist<'a, ScenarioTwo, K>;
 
 #[cfg(test)]
 mod tests {
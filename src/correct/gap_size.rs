@@ -26,14 +26,20 @@ use log::debug;
 /* local use */
 use crate::correct::*;
 
-pub struct GapSize<'a> {
-    valid_kmer: &'a set::BoxKmerSet<'a>,
-    graph: graph::Graph<'a>,
-    one: One<'a>,
+pub struct GapSize<'a, K = u64>
+where
+    K: kmer::Kmer,
+{
+    valid_kmer: &'a set::BoxKmerSet<'a, K>,
+    graph: graph::Graph<'a, K>,
+    one: One<'a, K>,
 }
 
-impl<'a> GapSize<'a> {
-    pub fn new(valid_kmer: &'a set::BoxKmerSet<'a>, c: u8) -> Self {
+impl<'a, K> GapSize<'a, K>
+where
+    K: kmer::Kmer,
+{
+    pub fn new(valid_kmer: &'a set::BoxKmerSet<'a, K>, c: u8) -> Self {
         Self {
             valid_kmer,
             graph: graph::Graph::new(valid_kmer),
@@ -41,12 +47,12 @@ impl<'a> GapSize<'a> {
         }
     }
 
-    pub fn ins_sub_correction(&self, kmer: u64, gap_size: usize) -> Option<(Vec<u8>, usize)> {
+    pub fn ins_sub_correction(&self, kmer: K, gap_size: usize) -> Outcome {
         let mut alts = alt_nucs(self.valid_kmer, kmer);
 
         if alts.len() != 1 {
             debug!("not one alts {:?}", alts);
-            return None;
+            return Outcome::from_alts(&alts);
         }
 
         let mut corr = add_nuc_to_end(kmer >> 2, alts[0], self.k());
@@ -60,24 +66,18 @@ impl<'a> GapSize<'a> {
             if alts.len() != 1 {
                 debug!(
                     "failled multiple successor {} {:?} i: {}",
-                    cocktail::kmer::kmer2seq(corr, self.valid_kmer.k()),
+                    corr.kmer2seq(self.valid_kmer.k()),
                     alts,
                     i
                 );
-                return None;
+                return Outcome::from_alts(&alts);
             }
 
             corr = add_nuc_to_end(corr, alts[0], self.k());
-            debug!(
-                "kmer {}",
-                cocktail::kmer::kmer2seq(corr, self.valid_kmer.k())
-            );
+            debug!("kmer {}", corr.kmer2seq(self.valid_kmer.k()));
             if viewed_kmer.contains(&corr) {
-                debug!(
-                    "we view this kmer previously {}",
-                    cocktail::kmer::kmer2seq(corr, self.k())
-                );
-                return None;
+                debug!("we view this kmer previously {}", corr.kmer2seq(self.k()));
+                return Outcome::CycleDetected;
             }
             viewed_kmer.insert(corr);
 
@@ -85,16 +85,23 @@ impl<'a> GapSize<'a> {
         }
 
         let offset = local_corr.len();
-        Some((local_corr, offset))
+        Outcome::Corrected(Correction::new(local_corr, offset, None))
     }
 }
 
-impl<'a> Corrector for GapSize<'a> {
-    fn valid_kmer(&self) -> &set::BoxKmerSet<'a> {
+impl<'a, K> Corrector<K> for GapSize<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn valid_kmer(&self) -> &set::BoxKmerSet<'a, K> {
         self.valid_kmer
     }
 
-    fn correct_error(&self, kmer: u64, seq: &[u8]) -> Option<(Vec<u8>, usize)> {
+    fn name(&self) -> &'static str {
+        "GapSize"
+    }
+
+    fn correct_error(&self, kmer: K, seq: &[u8]) -> Outcome {
         let (error_len, _first_correct_kmer) = error_len(seq, kmer, self.valid_kmer());
 
         debug!("error_len {}", error_len);
@@ -26,22 +26,35 @@ use log::debug;
 /* local use */
 use crate::correct::*;
 
-pub struct Graph<'a> {
-    valid_kmer: &'a set::BoxKmerSet<'a>,
+pub struct Graph<'a, K = u64>
+where
+    K: kmer::Kmer,
+{
+    valid_kmer: &'a set::BoxKmerSet<'a, K>,
 }
 
-impl<'a> Graph<'a> {
-    pub fn new(valid_kmer: &'a set::BoxKmerSet<'a>) -> Self {
+impl<'a, K> Graph<'a, K>
+where
+    K: kmer::Kmer,
+{
+    pub fn new(valid_kmer: &'a set::BoxKmerSet<'a, K>) -> Self {
         Self { valid_kmer }
     }
 }
 
-impl<'a> Corrector for Graph<'a> {
-    fn valid_kmer(&self) -> &set::BoxKmerSet {
+impl<'a, K> Corrector<K> for Graph<'a, K>
+where
+    K: kmer::Kmer,
+{
+    fn valid_kmer(&self) -> &set::BoxKmerSet<K> {
         self.valid_kmer
     }
 
-    fn correct_error(&self, mut kmer: u64, seq: &[u8]) -> Option<(Vec<u8>, usize)> {
+    fn name(&self) -> &'static str {
+        "Graph"
+    }
+
+    fn correct_error(&self, mut kmer: K, seq: &[u8]) -> Outcome {
         let (error_len, first_correct_kmer) = error_len(seq, kmer, self.valid_kmer());
 
         let mut viewed_kmer = rustc_hash::FxHashSet::default();
@@ -51,7 +64,7 @@ impl<'a> Corrector for Graph<'a> {
         let alts = alt_nucs(self.valid_kmer(), kmer);
         if alts.len() != 1 {
             debug!("failed multiple successor {:?}", alts);
-            return None;
+            return Outcome::from_alts(&alts);
         }
 
         kmer = add_nuc_to_end(kmer >> 2, alts[0], self.k());
@@ -63,14 +76,14 @@ impl<'a> Corrector for Graph<'a> {
 
             if alts.len() != 1 {
                 debug!("failed branching node {:?}", alts);
-                return None;
+                return Outcome::from_alts(&alts);
             }
 
             kmer = add_nuc_to_end(kmer, alts[0], self.k());
 
             if viewed_kmer.contains(&kmer) {
                 debug!("we view this kmer previously");
-                return None;
+                return Outcome::CycleDetected;
             }
             viewed_kmer.insert(kmer);
 
@@ -81,7 +94,7 @@ impl<'a> Corrector for Graph<'a> {
             }
         }
 
-        Some((local_corr, error_len + 1))
+        Outcome::Corrected(Correction::new(local_corr, error_len + 1, None))
     }
 }
 
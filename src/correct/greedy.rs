@@ -38,14 +38,20 @@ impl bio::alignment::pairwise::MatchFunc for Score {
     }
 }
 
-pub struct Greedy<'a> {
-    valid_kmer: &'a set::BoxKmerSet<'a>,
+pub struct Greedy<'a, K = u64>
+where
+    K: kmer::Kmer,
+{
+    valid_kmer: &'a set::BoxKmerSet<'a, K>,
     max_search: u8,
     nb_validate: u8,
 }
 
-impl<'a> Greedy<'a> {
-    pub fn new(valid_kmer: &'a set::BoxKmerSet, max_search: u8, nb_validate: u8) -> Self {
+impl<'a, K> Greedy<'a, K>
+where
+    K: kmer::Kmer,
+{
+    pub fn new(valid_kmer: &'a set::BoxKmerSet<K>, max_search: u8, nb_validate: u8) -> Self {
         Self {
             valid_kmer,
             max_search,
@@ -88,7 +94,7 @@ impl<'a> Greedy<'a> {
         None
     }
 
-    fn follow_graph(&self, mut kmer: u64) -> Option<(u8, u64)> {
+    fn follow_graph(&self, mut kmer: K) -> Option<(u8, K)> {
         let alts = next_nucs(self.valid_kmer(), kmer);
 
         if alts.len() != 1 {
@@ -101,7 +107,7 @@ impl<'a> Greedy<'a> {
         Some((cocktail::kmer::bit2nuc(alts[0]), kmer))
     }
 
-    fn check_next_kmers(&self, mut kmer: u64, seq: &[u8]) -> bool {
+    fn check_next_kmers(&self, mut kmer: K, seq: &[u8]) -> bool {
         if seq.len() < self.nb_validate as usize {
             return false;
         }
@@ -117,28 +123,33 @@ impl<'a> Greedy<'a> {
     }
 }
 
-impl<'a> Corrector for Greedy<'a> {
+impl<'a, K> Corrector<K> for Greedy<'a, K>
+where
+    K: kmer::Kmer,
+{
     fn k(&self) -> u8 {
         self.valid_kmer.k()
     }
 
-    fn valid_kmer(&self) -> &set::BoxKmerSet {
+    fn valid_kmer(&self) -> &set::BoxKmerSet<K> {
         self.valid_kmer
     }
 
-    fn correct_error(&self, mut kmer: u64, seq: &[u8]) -> Option<(Vec<u8>, usize)> {
+    fn name(&self) -> &'static str {
+        "Greedy"
+    }
+
+    fn correct_error(&self, mut kmer: K, seq: &[u8]) -> Outcome {
         let alts = alt_nucs(self.valid_kmer(), kmer);
         if alts.len() != 1 {
             debug!("failled multiple successor {:?}", alts);
-            return None;
+            return Outcome::from_alts(&alts);
         }
 
         let mut viewed_kmer = rustc_hash::FxHashSet::default();
 
         let mut local_corr = Vec::new();
-        let before_seq = cocktail::kmer::kmer2seq(kmer >> 2, self.k() - 1)
-            .as_bytes()
-            .to_vec();
+        let before_seq = (kmer >> 2).kmer2seq(self.k() - 1).as_bytes().to_vec();
 
         kmer = add_nuc_to_end(kmer >> 2, alts[0], self.k());
 
@@ -153,23 +164,23 @@ impl<'a> Corrector for Greedy<'a> {
 
             if viewed_kmer.contains(&kmer) {
                 debug!("we view this kmer previously");
-                return None;
+                return Outcome::CycleDetected;
             }
             viewed_kmer.insert(kmer);
 
             if seq.len() < i {
-                return None;
+                return Outcome::SearchExhausted;
             }
 
             if let Some(off) = self.match_alignement(before_seq.clone(), &seq[..i], &local_corr) {
                 if self.check_next_kmers(kmer, &seq[i..]) {
                     let offset: usize = (local_corr.len() as i64 + off) as usize;
-                    return Some((local_corr, offset));
+                    return Outcome::Corrected(Correction::new(local_corr, offset, None));
                 }
             }
         }
 
-        None
+        Outcome::SearchExhausted
     }
 }
 
@@ -20,102 +20,653 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
  */
 
+/* crate use */
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 /* local use */
+use crate::kmer;
 use crate::set;
 
-const MASK_LOOKUP: [u64; 32] = {
-    let mut lookup = [0; 32];
+/// Sequence longer than two time this value are split in chunks of this length corrected in parallel
+pub const CHUNK_LEN: usize = 1 << 17;
+
+/// Policy apply on non ACGT bases
+#[derive(
+    std::clone::Clone, std::marker::Copy, std::fmt::Debug, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub enum AmbiguousPolicy {
+    /// Correct each run of A, C, G, T independently, ambiguous bases are keep
+    Split,
+    /// Kmer with ambiguous base is never solid, correctors try to replace it
+    Resolve,
+}
+
+/// Configuration of a correction method with its own parameters
+#[derive(
+    std::fmt::Debug, std::clone::Clone, std::marker::Copy, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub enum MethodConfig {
+    /// One error scenario, confirm is number of kmer required to validate correction
+    One { confirm: u8 },
+
+    /// Two error scenario, confirm is number of kmer required to validate correction
+    Two { confirm: u8 },
+
+    /// Graph search of path between solid kmer
+    Graph,
+
+    /// Greedy search, max_search is number of base use to try correct error
+    Greedy { max_search: u8, confirm: u8 },
+
+    /// Correction base on size of weak kmer stretch
+    GapSize { confirm: u8 },
+}
 
-    let mut k = 1;
-    while k < 32 {
-        lookup[k] = (1 << (2 * k)) - 1;
+impl MethodConfig {
+    /// Get correction method of configuration
+    pub fn method(&self) -> Method {
+        match self {
+            MethodConfig::One { .. } => Method::One,
+            MethodConfig::Two { .. } => Method::Two,
+            MethodConfig::Graph => Method::Graph,
+            MethodConfig::Greedy { .. } => Method::Greedy,
+            MethodConfig::GapSize { .. } => Method::GapSize,
+        }
+    }
 
-        k += 1;
+    /// Build corrector against a solid kmer set
+    pub fn build<'a, K>(
+        &self,
+        solid: &'a set::BoxKmerSet<'a, K>,
+    ) -> Box<dyn Corrector<K> + Sync + Send + 'a>
+    where
+        K: kmer::Kmer,
+    {
+        match *self {
+            MethodConfig::One { confirm } => Box::new(One::new(solid, confirm)),
+            MethodConfig::Two { confirm } => Box::new(Two::new(solid, confirm)),
+            MethodConfig::Graph => Box::new(Graph::new(solid)),
+            MethodConfig::Greedy {
+                max_search,
+                confirm,
+            } => Box::new(Greedy::new(solid, max_search, confirm)),
+            MethodConfig::GapSize { confirm } => Box::new(GapSize::new(solid, confirm)),
+        }
     }
+}
+
+/// Correction method
+#[derive(
+    std::fmt::Debug, std::clone::Clone, std::marker::Copy, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub enum Method {
+    /// One error scenario
+    One,
+
+    /// Two error scenario
+    Two,
+
+    /// Graph search of path between solid kmer
+    Graph,
 
-    lookup
-};
+    /// Greedy search
+    Greedy,
 
+    /// Correction base on size of weak kmer stretch
+    GapSize,
+}
+
+impl Method {
+    /// Get configuration of correction method, parameters unused by method are ignored
+    pub fn config(&self, confirm: u8, max_search: u8) -> MethodConfig {
+        match self {
+            Method::One => MethodConfig::One { confirm },
+            Method::Two => MethodConfig::Two { confirm },
+            Method::Graph => MethodConfig::Graph,
+            Method::Greedy => MethodConfig::Greedy {
+                max_search,
+                confirm,
+            },
+            Method::GapSize => MethodConfig::GapSize { confirm },
+        }
+    }
+}
+
+/// Kmer size of solid set use by a correction method
+#[derive(std::fmt::Debug, std::clone::Clone, std::marker::Copy, std::cmp::PartialEq)]
+pub struct MethodKmerSize {
+    /// Correction method
+    pub method: Method,
+
+    /// Kmer size of solid set use by method
+    pub kmer_size: u8,
+}
+
+/// Return true if nucleotide is an unambiguous base
+#[inline(always)]
+pub fn is_acgt(nuc: u8) -> bool {
+    matches!(nuc, b'A' | b'C' | b'G' | b'T' | b'a' | b'c' | b'g' | b't')
+}
+
+/// Return complement of nucleotide, case is keep and non ACGT bases are return as is
 #[inline(always)]
-pub(crate) fn mask(k: u8) -> u64 {
-    MASK_LOOKUP[k as usize]
+pub fn complement(nuc: u8) -> u8 {
+    match nuc {
+        b'A' => b'T',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'T' => b'A',
+        b'a' => b't',
+        b'c' => b'g',
+        b'g' => b'c',
+        b't' => b'a',
+        other => other,
+    }
+}
+
+/// Reverse complement a sequence in place
+pub fn revcomp(seq: &mut [u8]) {
+    seq.reverse();
+    seq.iter_mut().for_each(|nuc| *nuc = complement(*nuc));
 }
 
-pub trait Corrector {
-    fn valid_kmer(&self) -> &set::BoxKmerSet;
+/// Local correction found by a corrector for one error
+#[derive(std::fmt::Debug, std::clone::Clone, std::cmp::PartialEq, std::cmp::Eq)]
+pub struct Correction {
+    /// Corrected bases
+    pub seq: Vec<u8>,
 
-    fn correct_error(&self, kmer: u64, seq: &[u8]) -> Option<(Vec<u8>, usize)>;
+    /// Number of read bases replaced by corrected bases
+    pub offset: usize,
+
+    /// Scenario use to found correction, if corrector use scenario
+    pub scenario: Option<String>,
+}
+
+impl Correction {
+    pub fn new(seq: Vec<u8>, offset: usize, scenario: Option<String>) -> Self {
+        Self {
+            seq,
+            offset,
+            scenario,
+        }
+    }
+}
+
+/// Outcome of a correction attempt
+#[derive(
+    std::fmt::Debug,
+    std::clone::Clone,
+    std::cmp::PartialEq,
+    std::cmp::Eq,
+    strum_macros::IntoStaticStr,
+)]
+pub enum Outcome {
+    /// Error is corrected
+    Corrected(Correction),
+
+    /// Corrector reach a kmer with more than one solid successor
+    Branching,
+
+    /// Corrector reach a kmer without any solid successor
+    NoAlternative,
+
+    /// Corrector reach a kmer already visited
+    CycleDetected,
+
+    /// No scenario can explain error
+    NoScenario,
+
+    /// More than one scenario can explain error
+    MultipleScenarios,
+
+    /// Corrector reach search limit without found correction
+    SearchExhausted,
+}
+
+impl Outcome {
+    /// Build failure outcome when number of alternative nucleotide isn't one
+    pub fn from_alts(alts: &[u64]) -> Self {
+        if alts.is_empty() {
+            Outcome::NoAlternative
+        } else {
+            Outcome::Branching
+        }
+    }
+
+    /// Return true if error is corrected
+    pub fn is_corrected(&self) -> bool {
+        matches!(self, Outcome::Corrected(_))
+    }
+
+    /// Get name of outcome
+    pub fn name(&self) -> &'static str {
+        self.into()
+    }
+}
+
+/// Correction attempt of a corrector on a sequence
+#[derive(std::fmt::Debug, std::clone::Clone, std::cmp::PartialEq, std::cmp::Eq)]
+pub struct Event {
+    /// Position of first weak kmer in sequence
+    pub pos: usize,
+
+    /// Name of corrector
+    pub method: &'static str,
+
+    /// Outcome of correction attempt
+    pub outcome: Outcome,
+}
+
+impl Event {
+    /// Convert event in edit, return None if event isn't a correction
+    pub fn to_edit(&self, seq: &[u8]) -> Option<Edit> {
+        match &self.outcome {
+            Outcome::Corrected(correction) => Some(Edit {
+                pos: self.pos,
+                original: seq[self.pos..std::cmp::min(self.pos + correction.offset, seq.len())]
+                    .to_vec(),
+                replacement: correction.seq.clone(),
+                method: self.method,
+                scenario: correction.scenario.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Convert event found on reverse complement of a sequence of length len in event on sequence
+    pub fn revcomp(mut self, len: usize) -> Self {
+        match &mut self.outcome {
+            Outcome::Corrected(correction) => {
+                correction.offset = std::cmp::min(correction.offset, len - self.pos);
+                self.pos = len - self.pos - correction.offset;
+                revcomp(&mut correction.seq);
+            }
+            _ => self.pos = len - self.pos - 1,
+        }
+
+        self
+    }
+}
+
+/// Edition apply on a sequence by a corrector
+#[derive(std::fmt::Debug, std::clone::Clone, std::cmp::PartialEq, std::cmp::Eq)]
+pub struct Edit {
+    /// Position of edition in sequence
+    pub pos: usize,
+
+    /// Bases of sequence replaced
+    pub original: Vec<u8>,
+
+    /// Bases insert in place of original bases
+    pub replacement: Vec<u8>,
+
+    /// Name of corrector
+    pub method: &'static str,
+
+    /// Scenario use by corrector
+    pub scenario: Option<String>,
+}
+
+pub trait Corrector<K = u64>: Sync
+where
+    K: kmer::Kmer,
+{
+    fn valid_kmer(&self) -> &set::BoxKmerSet<K>;
+
+    fn name(&self) -> &'static str;
+
+    fn correct_error(&self, kmer: K, seq: &[u8]) -> Outcome;
 
     fn k(&self) -> u8 {
         self.valid_kmer().k()
     }
 
     fn correct(&self, seq: &[u8]) -> Vec<u8> {
+        self.correct_events(seq).0
+    }
+
+    fn correct_edits(&self, seq: &[u8]) -> (Vec<u8>, Vec<Edit>) {
+        let (correct, events) = self.correct_events(seq);
+
+        (
+            correct,
+            events
+                .iter()
+                .filter_map(|event| event.to_edit(seq))
+                .collect(),
+        )
+    }
+
+    fn correct_ambiguous(&self, seq: &[u8], policy: AmbiguousPolicy) -> (Vec<u8>, Vec<Event>) {
+        match policy {
+            AmbiguousPolicy::Resolve => self.correct_events(seq),
+            AmbiguousPolicy::Split => self.correct_split(seq),
+        }
+    }
+
+    /// Correct each run of unambiguous bases independently, ambiguous bases are keep as is
+    fn correct_split(&self, seq: &[u8]) -> (Vec<u8>, Vec<Event>) {
         let mut correct: Vec<u8> = Vec::with_capacity(seq.len());
+        let mut events: Vec<Event> = Vec::new();
+
+        let mut begin = 0;
+        while begin < seq.len() {
+            let end = seq[begin..]
+                .iter()
+                .position(|nuc| !is_acgt(*nuc))
+                .map_or(seq.len(), |p| begin + p);
+
+            let (run, run_events) = self.correct_events(&seq[begin..end]);
+            correct.extend(run);
+            events.extend(run_events.into_iter().map(|mut event| {
+                event.pos += begin;
+                event
+            }));
+
+            let next = seq[end..]
+                .iter()
+                .position(|nuc| is_acgt(*nuc))
+                .map_or(seq.len(), |p| end + p);
+            correct.extend(&seq[end..next]);
+
+            begin = next;
+        }
+
+        (correct, events)
+    }
+
+    /// Correct sequence, bases before first solid kmer are correct leftwards from it
+    fn correct_events(&self, seq: &[u8]) -> (Vec<u8>, Vec<Event>) {
+        let k = self.k() as usize;
+
+        let anchor = match self.anchor(seq) {
+            Some(anchor) if anchor > 0 => anchor,
+            _ => return self.correct_forward(seq),
+        };
+
+        // prefix is correct on reverse complement so anchor is the first kmer
+        let mut prefix = seq[..anchor + k].to_vec();
+        revcomp(&mut prefix);
+
+        let (mut correct, prefix_events) = self.correct_forward(&prefix);
+        revcomp(&mut correct);
+        correct.truncate(correct.len() - k);
+
+        let mut events: Vec<Event> = prefix_events
+            .into_iter()
+            .rev()
+            .map(|event| event.revcomp(prefix.len()))
+            .collect();
+
+        let (suffix, suffix_events) = self.correct_forward(&seq[anchor..]);
+        correct.extend(suffix);
+        events.extend(suffix_events.into_iter().map(|mut event| {
+            event.pos += anchor;
+            event
+        }));
+
+        (correct, events)
+    }
+
+    /// Position of first solid kmer without ambiguous base, kmer is roll along sequence
+    fn anchor(&self, seq: &[u8]) -> Option<usize> {
+        let k = self.k() as usize;
+        if seq.len() < k {
+            return None;
+        }
+
+        let mut kmer = K::seq2bit(&seq[0..k]);
+
+        // number of next kmer, current included, contains an ambiguous base
+        let mut ambiguous = seq[0..k]
+            .iter()
+            .rposition(|nuc| !is_acgt(*nuc))
+            .map_or(0, |p| p + 1);
+
+        for pos in 0..=(seq.len() - k) {
+            if pos > 0 {
+                let nuc = seq[pos + k - 1];
+
+                kmer = add_nuc_to_end(kmer, cocktail::kmer::nuc2bit(nuc), self.k());
+                ambiguous = if is_acgt(nuc) {
+                    ambiguous.saturating_sub(1)
+                } else {
+                    k
+                };
+            }
+
+            if ambiguous == 0 && self.valid_kmer().get(kmer) {
+                return Some(pos);
+            }
+        }
+
+        None
+    }
+
+    /// Correct sequence from first kmer, kmer with an ambiguous base is never solid so corrector try to resolve it
+    fn correct_forward(&self, seq: &[u8]) -> (Vec<u8>, Vec<Event>) {
+        #[cfg(feature = "parallel")]
+        if seq.len() > 2 * CHUNK_LEN {
+            return self.correct_forward_chunked(seq, CHUNK_LEN);
+        }
+
+        let (correct, events, _) = self.correct_forward_sync(seq, &[]);
+
+        (correct, events)
+    }
+
+    /// Same result as correct_forward, sequence is split in chunks begin on a solid kmer and chunks are corrected in parallel
+    fn correct_forward_chunked(&self, seq: &[u8], chunk_len: usize) -> (Vec<u8>, Vec<Event>) {
+        let k = self.k() as usize;
+
+        // chunks begin on first solid kmer after each multiple of chunk_len
+        let mut starts = vec![0];
+        let mut target = chunk_len.max(1);
+        while target + k <= seq.len() {
+            match self.anchor(&seq[target..]) {
+                Some(pos) => {
+                    starts.push(target + pos);
+                    target += pos + chunk_len.max(1);
+                }
+                None => break,
+            }
+        }
+
+        #[cfg(feature = "parallel")]
+        let iter = starts.par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = starts.iter();
+
+        let mut chunks: Vec<_> = iter
+            .enumerate()
+            .map(|(c, start)| {
+                let syncs: Vec<usize> = starts[c + 1..].iter().map(|s| s - start).collect();
+                let (correct, events, sync) = self.correct_forward_sync(&seq[*start..], &syncs);
+
+                Some((correct, events, sync.map(|s| c + 1 + s)))
+            })
+            .collect();
+
+        // follow chunks from first one, a chunk stop where it reach next chunk in same state so stitching is identical to a sequential walk
+        let mut correct = Vec::with_capacity(seq.len());
+        let mut events = Vec::new();
+
+        let mut c = 0;
+        while let Some((chunk, chunk_events, next)) = chunks[c].take() {
+            correct.extend(chunk);
+            events.extend(chunk_events.into_iter().map(|mut event| {
+                event.pos += starts[c];
+                event
+            }));
+
+            match next {
+                Some(next) => {
+                    correct.truncate(correct.len() - k);
+                    c = next;
+                }
+                None => break,
+            }
+        }
+
+        (correct, events)
+    }
+
+    /// Correct sequence from first kmer, walk stop at first sync position reach in same state as a walk begin on it, index of this sync position is return
+    fn correct_forward_sync(
+        &self,
+        seq: &[u8],
+        syncs: &[usize],
+    ) -> (Vec<u8>, Vec<Event>, Option<usize>) {
+        let mut correct: Vec<u8> = Vec::with_capacity(seq.len());
+        let mut events: Vec<Event> = Vec::new();
 
         if seq.len() < self.k() as usize {
-            return seq.to_vec();
+            return (seq.to_vec(), events, None);
         }
 
         let mut i = self.k() as usize;
-        let mut kmer = cocktail::kmer::seq2bit(&seq[0..i]);
+        let mut kmer = K::seq2bit(&seq[0..i]);
 
         for n in &seq[0..i] {
             correct.push(*n);
         }
 
-        let mut previous = self.valid_kmer().get(kmer);
+        // number of next kmer, current included, contains an ambiguous base
+        let mut ambiguous = seq[0..i]
+            .iter()
+            .rposition(|nuc| !is_acgt(*nuc))
+            .map_or(0, |p| p + 1);
+
+        let k = self.k() as usize;
+        let mut next_sync = 0;
+
+        let mut previous = ambiguous == 0 && self.valid_kmer().get(kmer);
         while i < seq.len() {
+            while next_sync < syncs.len() && syncs[next_sync] + k < i {
+                next_sync += 1;
+            }
+
+            if let Some(sync) = syncs.get(next_sync).filter(|sync| *sync + k == i) {
+                let window = &seq[*sync..i];
+
+                if previous
+                    && ambiguous == 0
+                    && kmer == K::seq2bit(window)
+                    && &correct[correct.len() - k..] == window
+                {
+                    return (correct, events, Some(next_sync));
+                }
+            }
+
             let nuc = seq[i];
 
             kmer = add_nuc_to_end(kmer, cocktail::kmer::nuc2bit(nuc), self.k());
+            ambiguous = if is_acgt(nuc) {
+                ambiguous.saturating_sub(1)
+            } else {
+                self.k() as usize
+            };
+
+            let solid = ambiguous == 0 && self.valid_kmer().get(kmer);
+            if !solid && previous {
+                let outcome = self.correct_error(kmer, &seq[i..]);
 
-            if !self.valid_kmer().get(kmer) && previous {
-                if let Some((local_correct, offset)) = self.correct_error(kmer, &seq[i..]) {
+                if let Outcome::Corrected(ref correction) = outcome {
                     kmer >>= 2;
 
-                    for nuc in local_correct {
+                    for nuc in correction.seq.iter() {
                         kmer = add_nuc_to_end(
                             kmer,
-                            cocktail::kmer::nuc2bit(nuc),
+                            cocktail::kmer::nuc2bit(*nuc),
                             self.valid_kmer().k(),
                         );
-                        correct.push(nuc);
+                        correct.push(*nuc);
                     }
 
                     log::debug!("error at position {} cor", i);
 
+                    let offset = correction.offset;
+                    events.push(Event {
+                        pos: i,
+                        method: self.name(),
+                        outcome,
+                    });
+
+                    ambiguous = 0;
                     previous = true;
                     i += offset;
                 } else {
                     correct.push(nuc);
 
-                    log::debug!("error at position {} not", i);
+                    log::debug!("error at position {} not {:?}", i, outcome);
+
+                    events.push(Event {
+                        pos: i,
+                        method: self.name(),
+                        outcome,
+                    });
 
                     i += 1;
                     previous = false;
                 }
             } else {
-                previous = self.valid_kmer().get(kmer);
+                previous = solid;
                 correct.push(nuc);
 
                 i += 1;
             }
         }
 
-        correct
+        (correct, events, None)
+    }
+}
+
+/// Apply edits on a per base vector, replaced base keep their value, inserted base get value return by fill and deleted base lost their value
+pub fn realign<T, F>(values: &[T], edits: &[Edit], fill: F) -> Vec<T>
+where
+    T: std::clone::Clone,
+    F: Fn(&Edit) -> T,
+{
+    let mut realign = Vec::with_capacity(values.len());
+
+    let mut prev = 0;
+    for edit in edits {
+        let end = std::cmp::min(edit.pos + edit.original.len(), values.len());
+        let keep = std::cmp::min(edit.original.len(), edit.replacement.len());
+
+        realign.extend_from_slice(&values[prev..edit.pos]);
+        realign.extend_from_slice(&values[edit.pos..std::cmp::min(edit.pos + keep, end)]);
+        for _ in keep..edit.replacement.len() {
+            realign.push(fill(edit));
+        }
+
+        prev = end;
     }
+    realign.extend_from_slice(&values[prev..]);
+
+    realign
 }
 
-pub(crate) fn add_nuc_to_end(kmer: u64, nuc: u64, k: u8) -> u64 {
-    ((kmer << 2) & mask(k)) ^ nuc
+pub(crate) fn add_nuc_to_end<K>(kmer: K, nuc: u64, k: u8) -> K
+where
+    K: kmer::Kmer,
+{
+    ((kmer << 2) & K::mask(k)) ^ K::from_nuc(nuc)
 }
 
-pub(crate) fn alt_nucs(valid_kmer: &set::BoxKmerSet, ori: u64) -> Vec<u64> {
+pub(crate) fn alt_nucs<K>(valid_kmer: &set::BoxKmerSet<K>, ori: K) -> Vec<u64>
+where
+    K: kmer::Kmer,
+{
     next_nucs(valid_kmer, ori >> 2)
 }
 
-pub(crate) fn next_nucs(valid_kmer: &set::BoxKmerSet, kmer: u64) -> Vec<u64> {
+pub(crate) fn next_nucs<K>(valid_kmer: &set::BoxKmerSet<K>, kmer: K) -> Vec<u64>
+where
+    K: kmer::Kmer,
+{
     let mut correct_nuc: Vec<u64> = Vec::with_capacity(4);
 
     for alt_nuc in 0..4 {
@@ -127,12 +678,19 @@ pub(crate) fn next_nucs(valid_kmer: &set::BoxKmerSet, kmer: u64) -> Vec<u64> {
     correct_nuc
 }
 
-pub(crate) fn error_len(
+pub(crate) fn error_len<K>(
     subseq: &[u8],
-    mut kmer: u64,
-    valid_kmer: &set::BoxKmerSet,
-) -> (usize, u64) {
+    mut kmer: K,
+    valid_kmer: &set::BoxKmerSet<K>,
+) -> (usize, K)
+where
+    K: kmer::Kmer,
+{
     let mut j = 0;
+    let mut ambiguous = match subseq.first() {
+        Some(nuc) if !is_acgt(*nuc) => valid_kmer.k() as usize,
+        _ => 0,
+    };
 
     loop {
         j += 1;
@@ -142,8 +700,13 @@ pub(crate) fn error_len(
         }
 
         kmer = add_nuc_to_end(kmer, cocktail::kmer::nuc2bit(subseq[j]), valid_kmer.k());
+        ambiguous = if is_acgt(subseq[j]) {
+            ambiguous.saturating_sub(1)
+        } else {
+            valid_kmer.k() as usize
+        };
 
-        if valid_kmer.get(kmer) {
+        if ambiguous == 0 && valid_kmer.get(kmer) {
             break;
         }
     }
@@ -179,4 +742,316 @@ mod tests {
 
         assert_eq!(alt_nucs(&set, kmer), vec![0, 2]);
     }
+
+    #[test]
+    fn reverse_complement() {
+        let mut seq = b"ACGTNacgt".to_vec();
+        revcomp(&mut seq);
+
+        assert_eq!(seq, b"acgtNACGT".to_vec());
+    }
+
+    #[test]
+    fn anchor() {
+        let refe = b"ACGTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTG";
+        let read = b"ACTTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTG";
+
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(refe, 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+        let corrector = One::new(&set, 2);
+
+        assert_eq!(corrector.anchor(read), Some(3));
+        assert_eq!(corrector.anchor(refe), Some(0));
+        assert_eq!(corrector.anchor(b"NNNNNNN"), None);
+        assert_eq!(corrector.anchor(b"ACG"), None);
+        assert_eq!(corrector.anchor(b"ANGTGGGAATTG"), Some(2));
+        assert_eq!(corrector.anchor(b"ACGNGGGAATTG"), Some(4));
+
+        let (seq, edits) = corrector.correct_edits(read);
+        assert_eq!(seq, refe.to_vec());
+        assert_eq!(
+            edits,
+            vec![Edit {
+                pos: 2,
+                original: b"T".to_vec(),
+                replacement: b"G".to_vec(),
+                method: "One",
+                scenario: Some("ScenarioOne::S".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn realign_values() {
+        let edit = |pos: usize, original: &[u8], replacement: &[u8]| Edit {
+            pos,
+            original: original.to_vec(),
+            replacement: replacement.to_vec(),
+            method: "test",
+            scenario: None,
+        };
+
+        let values = b"ABCDEF";
+
+        assert_eq!(realign(values, &[], |_| b'!'), b"ABCDEF".to_vec());
+        assert_eq!(
+            realign(values, &[edit(2, b"T", b"A")], |_| b'!'),
+            b"ABCDEF".to_vec()
+        );
+        assert_eq!(
+            realign(values, &[edit(2, b"TT", b"A")], |_| b'!'),
+            b"ABCEF".to_vec()
+        );
+        assert_eq!(
+            realign(values, &[edit(2, b"", b"A")], |_| b'!'),
+            b"AB!CDEF".to_vec()
+        );
+        assert_eq!(
+            realign(values, &[edit(1, b"T", b"AA"), edit(4, b"T", b"")], |_| {
+                b'!'
+            }),
+            b"AB!CDF".to_vec()
+        );
+        assert_eq!(
+            realign(values, &[edit(6, b"", b"A")], |e| e.pos as u8),
+            b"ABCDEF\x06".to_vec()
+        );
+    }
+
+    #[test]
+    fn edits() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACTGACGACCC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+        let corrector = One::new(&set, 2);
+
+        // substitution
+        let (seq, edits) = corrector.correct_edits(b"ACTGATGACCC");
+        assert_eq!(seq, b"ACTGACGACCC".to_vec());
+        assert_eq!(
+            edits,
+            vec![Edit {
+                pos: 5,
+                original: b"T".to_vec(),
+                replacement: b"C".to_vec(),
+                method: "One",
+                scenario: Some("ScenarioOne::S".to_string()),
+            }]
+        );
+        assert_eq!(realign(b"ABCDEFGHIJK", &edits, |_| b'!'), b"ABCDEFGHIJK");
+
+        // insertion
+        let (seq, edits) = corrector.correct_edits(b"ACTGATCGACCC");
+        assert_eq!(seq, b"ACTGACGACCC".to_vec());
+        assert_eq!(
+            edits,
+            vec![Edit {
+                pos: 5,
+                original: b"TC".to_vec(),
+                replacement: b"C".to_vec(),
+                method: "One",
+                scenario: Some("ScenarioOne::I".to_string()),
+            }]
+        );
+        assert_eq!(realign(b"ABCDEFGHIJKL", &edits, |_| b'!'), b"ABCDEFHIJKL");
+
+        // deletion
+        let (seq, edits) = corrector.correct_edits(b"ACTGAGACCC");
+        assert_eq!(seq, b"ACTGACGACCC".to_vec());
+        assert_eq!(
+            edits,
+            vec![Edit {
+                pos: 5,
+                original: b"".to_vec(),
+                replacement: b"C".to_vec(),
+                method: "One",
+                scenario: Some("ScenarioOne::D".to_string()),
+            }]
+        );
+        assert_eq!(realign(b"ABCDEFGHIJ", &edits, |_| b'!'), b"ABCDE!FGHIJ");
+
+        // no error
+        let (seq, edits) = corrector.correct_edits(b"ACTGACGACCC");
+        assert_eq!(seq, b"ACTGACGACCC".to_vec());
+        assert!(edits.is_empty());
+    }
+
+    #[test]
+    fn events() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"TCTTTATTTTC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+        let corrector = Graph::new(&set);
+
+        let (seq, events) = corrector.correct_events(b"TCTTTGTTTTC");
+        assert_eq!(seq, b"TCTTTATTTTC".to_vec());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].pos, 5);
+        assert_eq!(events[0].method, "Graph");
+        assert!(events[0].outcome.is_corrected());
+
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"TCTTTATTTTC", 5) {
+            data.set(kmer, true);
+        }
+        data.set(cocktail::kmer::seq2bit(b"TTTTT"), true);
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+        let corrector = Graph::new(&set);
+
+        let (seq, events) = corrector.correct_events(b"TCTTTGTTTTC");
+        assert_eq!(seq, b"TCTTTGTTTTC".to_vec());
+        assert_eq!(
+            events,
+            vec![Event {
+                pos: 5,
+                method: "Graph",
+                outcome: Outcome::Branching,
+            }]
+        );
+    }
+
+    #[test]
+    fn ambiguous() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"TCTTTATTTTC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+        let corrector = Graph::new(&set);
+
+        let (seq, events) = corrector.correct_ambiguous(b"TCTTTNTTTTC", AmbiguousPolicy::Resolve);
+        assert_eq!(seq, b"TCTTTATTTTC".to_vec());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].pos, 5);
+
+        let (seq, events) = corrector.correct_ambiguous(b"TCTTTNTTTTC", AmbiguousPolicy::Split);
+        assert_eq!(seq, b"TCTTTNTTTTC".to_vec());
+        assert!(events.is_empty());
+
+        let (seq, events) = corrector.correct_ambiguous(b"NTCTTTGTTTTCN", AmbiguousPolicy::Split);
+        assert_eq!(seq, b"NTCTTTATTTTCN".to_vec());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].pos, 6);
+    }
+
+    #[test]
+    fn ambiguous_not_solid() {
+        // N is encode like G, kmer with N must not match solid kmer with G
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"TCTTTGTTTTC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+        let corrector = Graph::new(&set);
+
+        let (seq, events) = corrector.correct_events(b"TCTTTNTTTTC");
+        assert_eq!(seq, b"TCTTTGTTTTC".to_vec());
+        assert_eq!(events.len(), 1);
+
+        assert!(is_acgt(b'a'));
+        assert!(!is_acgt(b'N'));
+        assert!(!is_acgt(b'R'));
+    }
+
+    #[test]
+    fn wide_kmer() {
+        let refe = b"ACGTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTGACGACTGTAAAGCGAGTGGCCGTGGAATTTCAAGCTCAATTAGCCGAACCAATCCGCCTA";
+        let mut read = refe.to_vec();
+        read[50] = b'T';
+
+        let mut fasta = b">1\n".to_vec();
+        fasta.extend_from_slice(refe);
+
+        let set: set::BoxKmerSet<u128> = Box::new(
+            set::Hash::<u128>::from_fasta(
+                std::io::Cursor::new(fasta),
+                "kmers.fasta".to_string(),
+                41,
+            )
+            .unwrap(),
+        );
+        let corrector = Graph::new(&set);
+
+        let (seq, events) = corrector.correct_events(&read);
+        assert_eq!(seq, refe.to_vec());
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].pos, 50);
+        assert_eq!(corrector.correct(refe).as_slice(), refe);
+    }
+
+    #[test]
+    fn outcome_from_alts() {
+        assert_eq!(Outcome::from_alts(&[]), Outcome::NoAlternative);
+        assert_eq!(Outcome::from_alts(&[0, 2]), Outcome::Branching);
+        assert!(!Outcome::Branching.is_corrected());
+        assert_eq!(Outcome::CycleDetected.name(), "CycleDetected");
+    }
+
+    #[test]
+    fn chunked() {
+        // deterministic pseudo random genome
+        let mut state: u64 = 42;
+        let mut genome = Vec::with_capacity(5000);
+        for _ in 0..5000 {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            genome.push(b"ACGT"[(state >> 33) as usize % 4]);
+        }
+
+        let mut data = pcon::solid::Solid::new(11);
+        for kmer in cocktail::tokenizer::Tokenizer::new(&genome, 11) {
+            data.set(kmer, true);
+        }
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        // substitution, deletion, insertion and ambiguous base near chunk border
+        let mut read = genome.clone();
+        read[150] = if read[150] == b'A' { b'C' } else { b'A' };
+        read.remove(403);
+        read.insert(799, b'T');
+        read[1001] = b'N';
+        for pos in (1500..read.len()).step_by(97) {
+            read[pos] = if read[pos] == b'G' { b'T' } else { b'G' };
+        }
+
+        let methods: Vec<Box<dyn Corrector + Sync + Send>> = vec![
+            MethodConfig::One { confirm: 2 }.build(&set),
+            MethodConfig::Two { confirm: 2 }.build(&set),
+            MethodConfig::Graph.build(&set),
+            MethodConfig::Greedy {
+                max_search: 7,
+                confirm: 2,
+            }
+            .build(&set),
+            MethodConfig::GapSize { confirm: 2 }.build(&set),
+        ];
+
+        for method in methods.iter() {
+            let (correct, events, _) = method.correct_forward_sync(&read, &[]);
+
+            for chunk_len in [1, 50, 100, 400, 1000, 10000] {
+                assert_eq!(
+                    method.correct_forward_chunked(&read, chunk_len),
+                    (correct.clone(), events.clone()),
+                    "method {} chunk {}",
+                    method.name(),
+                    chunk_len
+                );
+            }
+        }
+    }
 }
@@ -39,6 +39,131 @@ pub enum Error {
     /// In solid mode csv, fasta and fastq format require kmer size
     #[error("In solid mode csv, fasta and fastq format require kmer size")]
     SolidRequireKmerSize,
+
+    /// Kmer size is larger than largest kmer representation
+    #[error("Kmer size {0} is larger than maximal kmer size 64")]
+    KmerSizeTooLarge(u64),
+
+    /// Kmer size is null
+    #[error("Kmer size must be greater than 0")]
+    KmerSizeNull,
+
+    /// Error in configuration file parsing
+    #[error(transparent)]
+    ConfigParse(#[from] toml::de::Error),
+
+    /// Error in configuration serialization
+    #[error(transparent)]
+    ConfigDump(#[from] toml::ser::Error),
+
+    /// No subcommand set on command line or in configuration file
+    #[error("A subcommand must be set on command line or in configuration file")]
+    MissingSubCommand,
+
+    /// Record of an input can't be parse
+    #[error("Invalid record {index} of input {name} at byte {offset}: {error}")]
+    InvalidRecord {
+        /// Name of input
+        name: String,
+        /// Index of record in input
+        index: u64,
+        /// Offset in byte of record begin in decompressed input
+        offset: u64,
+        /// Parsing error
+        error: std::io::Error,
+    },
+
+    /// Memory mapped solid file is invalid
+    #[error("Memory mapped solid file is invalid, it must be write by br in mmap format")]
+    InvalidMmapSolid,
+
+    /// Kmer size of memory mapped solid file isn't kmer size requested by user
+    #[error("Memory mapped solid file contains kmer of size {found} but kmer size {expected} is requested")]
+    MmapKmerSizeMismatch {
+        /// Kmer size requested
+        expected: u8,
+        /// Kmer size in file
+        found: u8,
+    },
+
+    /// Memory mapped bitfield solid file is invalid
+    #[error(
+        "Memory mapped bitfield solid file is invalid, it must be an uncompressed pcon solid file"
+    )]
+    InvalidBitfieldSolid,
+
+    /// Memory mapped solid file is compressed
+    #[error("Memory mapped solid file is compressed, decompress it or use solid format")]
+    CompressedMmapSolid,
+
+    /// Number of inputs and outputs didn't match
+    #[error("Number of inputs {inputs} didn't match number of outputs {outputs}")]
+    InputOutputMismatch {
+        /// Number of inputs
+        inputs: usize,
+        /// Number of outputs
+        outputs: usize,
+    },
+
+    /// A correction worker stop without send its result
+    #[cfg(feature = "parallel")]
+    #[error("A correction worker stop without send its result")]
+    CorrectionWorkerStop,
+
+    /// Output writer stop before end of correction
+    #[cfg(feature = "parallel")]
+    #[error("Output writer stop before end of correction")]
+    WriterStop,
+
+    /// Output template or output directory is use without input path
+    #[error("Output template and output directory require input path")]
+    OutputTemplateRequireInputs,
+
+    /// Same output path is use for several inputs
+    #[error("Output {} is use for several inputs", .0.display())]
+    DuplicateOutput(std::path::PathBuf),
+
+    /// Output path is also an input path
+    #[error("Output {} is also an input", .0.display())]
+    OutputIsInput(std::path::PathBuf),
+
+    /// No solid set is available to build correction methods
+    #[error("No solid kmer set is available for correction method")]
+    NoSolidSet,
+
+    /// No solid set match kmer size assign to a correction method
+    #[error("No solid kmer set with kmer size {0} is available for correction method")]
+    NoSolidWithKmerSize(u8),
+
+    /// Kff file didn't store kmer size
+    #[cfg(feature = "kff")]
+    #[error("Kff file not contains kmer size in a value section")]
+    KffMissingKmerSize,
+
+    /// Kmer size of kff file isn't kmer size requested by user
+    #[cfg(feature = "kff")]
+    #[error("Kff file contains kmer of size {found} but kmer size {expected} is requested")]
+    KffKmerSizeMismatch {
+        /// Kmer size requested
+        expected: u8,
+        /// Kmer size in file
+        found: u8,
+    },
+
+    /// Kff kmer contains less nucleotide than kmer size
+    #[cfg(feature = "kff")]
+    #[error("Kff file contains kmer shorter than kmer size")]
+    KffKmerTooShort,
+
+    /// Error in kff parsing
+    #[cfg(feature = "kff")]
+    #[error(transparent)]
+    Kff(#[from] kff::error::Error),
+
+    /// Input look like fastq but br was build without fastq support
+    #[cfg(not(feature = "fastq"))]
+    #[error("Input look like fastq, br must be build with feature fastq to correct it")]
+    FastqRequireFeature,
 }
 
 /// Alias of result
@@ -0,0 +1,96 @@
+//! Annotation and filter of read by ratio of solid kmer
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::correct;
+use crate::kmer;
+use crate::set;
+
+/// Solid kmer ratio parameter
+#[derive(std::fmt::Debug, std::clone::Clone, std::marker::Copy, std::cmp::PartialEq)]
+pub struct SolidRatio {
+    /// Add ratio before and after correction in read description
+    pub annotate: bool,
+
+    /// Read with a ratio after correction lower than this value are drop
+    pub min: Option<f64>,
+}
+
+impl SolidRatio {
+    /// Create a new solid ratio parameter
+    pub fn new(annotate: bool, min: Option<f64>) -> Self {
+        Self { annotate, min }
+    }
+
+    /// Return true if read with this ratio after correction must be write
+    pub fn keep(&self, after: f64) -> bool {
+        self.min.map_or(true, |min| after >= min)
+    }
+
+    /// Build description annotation
+    pub fn annotation(before: f64, after: f64) -> String {
+        format!("solid_before={:.2} solid_after={:.2}", before, after)
+    }
+}
+
+/// Fraction of kmer of sequence present in solid set, kmer with an ambiguous base is never solid
+pub fn solid_ratio<K>(seq: &[u8], valid_kmer: &set::BoxKmerSet<K>) -> f64
+where
+    K: kmer::Kmer,
+{
+    let k = valid_kmer.k();
+    if seq.len() < k as usize {
+        return 0.0;
+    }
+
+    let mut kmer = K::from_nuc(0);
+    // number of next kmer, current included, contains an ambiguous base
+    let mut ambiguous = k as usize;
+    let mut solid = 0;
+
+    for (i, nuc) in seq.iter().enumerate() {
+        kmer = correct::add_nuc_to_end(kmer, cocktail::kmer::nuc2bit(*nuc), k);
+        ambiguous = if correct::is_acgt(*nuc) {
+            ambiguous.saturating_sub(1)
+        } else {
+            k as usize
+        };
+
+        if i + 1 >= k as usize && ambiguous == 0 && valid_kmer.get(kmer) {
+            solid += 1;
+        }
+    }
+
+    solid as f64 / (seq.len() - k as usize + 1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ratio() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACGTGGGAATTG", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        assert_eq!(solid_ratio(b"ACGTGGGAATTG", &set), 1.0);
+        assert_eq!(solid_ratio(b"ACGTGNGAATTG", &set), 0.375);
+        assert_eq!(solid_ratio(b"ACG", &set), 0.0);
+
+        let filter = SolidRatio::new(true, Some(0.5));
+        assert!(filter.keep(0.5));
+        assert!(!filter.keep(0.25));
+        assert!(SolidRatio::new(true, None).keep(0.0));
+        assert_eq!(
+            SolidRatio::annotation(0.8211, 0.97),
+            "solid_before=0.82 solid_after=0.97"
+        );
+    }
+}
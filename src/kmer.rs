@@ -0,0 +1,279 @@
+//! Kmer representation, u64 store kmer up to 32 bases and u128 up to 64 bases
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+
+/// Integer use to store a 2 bits encoded kmer
+pub trait Kmer:
+    std::marker::Copy
+    + std::fmt::Debug
+    + std::cmp::Eq
+    + std::cmp::Ord
+    + std::hash::Hash
+    + std::marker::Send
+    + std::marker::Sync
+    + std::ops::Shl<usize, Output = Self>
+    + std::ops::Shr<usize, Output = Self>
+    + std::ops::ShrAssign<usize>
+    + std::ops::BitAnd<Output = Self>
+    + std::ops::BitOr<Output = Self>
+    + std::ops::BitXor<Output = Self>
+    + 'static
+{
+    /// Maximal kmer size can be store
+    const MAX_K: u8;
+
+    /// Convert 2 bits encoded nucleotide in kmer
+    fn from_nuc(nuc: u64) -> Self;
+
+    /// Get 2 bits encoded last nucleotide of kmer
+    fn last_nuc(self) -> u64;
+
+    /// Mask to keep only 2 * k lower bits
+    fn mask(k: u8) -> Self;
+
+    /// Convert a sequence in kmer
+    fn seq2bit(seq: &[u8]) -> Self {
+        seq.iter().fold(Self::from_nuc(0), |kmer, nuc| {
+            (kmer << 2) | Self::from_nuc(cocktail::kmer::nuc2bit(*nuc))
+        })
+    }
+
+    /// Get reverse complement of kmer
+    fn revcomp(mut self, k: u8) -> Self {
+        let mut rev = Self::from_nuc(0);
+
+        for _ in 0..k {
+            rev = (rev << 2) | Self::from_nuc(self.last_nuc() ^ 0b10);
+            self >>= 2;
+        }
+
+        rev
+    }
+
+    /// Get canonical version of kmer, the minimum of kmer and its reverse complement
+    fn canonical(self, k: u8) -> Self {
+        std::cmp::min(self, self.revcomp(k))
+    }
+
+    /// Convert kmer in an u128
+    fn to_u128(self) -> u128;
+
+    /// Convert an u128 in kmer, upper bits that kmer can't store are drop
+    fn from_u128(value: u128) -> Self;
+
+    /// Convert kmer in sequence
+    fn kmer2seq(mut self, k: u8) -> String {
+        let mut seq = vec![b'A'; k as usize];
+
+        for nuc in seq.iter_mut().rev() {
+            *nuc = cocktail::kmer::bit2nuc(self.last_nuc());
+            self >>= 2;
+        }
+
+        String::from_utf8(seq).unwrap()
+    }
+}
+
+const MASK_LOOKUP: [u64; 32] = {
+    let mut lookup = [0; 32];
+
+    let mut k = 1;
+    while k < 32 {
+        lookup[k] = (1 << (2 * k)) - 1;
+
+        k += 1;
+    }
+
+    lookup
+};
+
+impl Kmer for u64 {
+    const MAX_K: u8 = 32;
+
+    #[inline(always)]
+    fn from_nuc(nuc: u64) -> Self {
+        nuc
+    }
+
+    #[inline(always)]
+    fn last_nuc(self) -> u64 {
+        self & 0b11
+    }
+
+    #[inline(always)]
+    fn mask(k: u8) -> Self {
+        MASK_LOOKUP.get(k as usize).copied().unwrap_or(u64::MAX)
+    }
+
+    #[inline(always)]
+    fn to_u128(self) -> u128 {
+        self as u128
+    }
+
+    #[inline(always)]
+    fn from_u128(value: u128) -> Self {
+        value as u64
+    }
+
+    fn seq2bit(seq: &[u8]) -> Self {
+        cocktail::kmer::seq2bit(seq)
+    }
+
+    fn revcomp(self, k: u8) -> Self {
+        cocktail::kmer::revcomp(self, k)
+    }
+
+    fn kmer2seq(self, k: u8) -> String {
+        cocktail::kmer::kmer2seq(self, k)
+    }
+}
+
+impl Kmer for u128 {
+    const MAX_K: u8 = 64;
+
+    #[inline(always)]
+    fn from_nuc(nuc: u64) -> Self {
+        nuc as u128
+    }
+
+    #[inline(always)]
+    fn last_nuc(self) -> u64 {
+        (self & 0b11) as u64
+    }
+
+    #[inline(always)]
+    fn mask(k: u8) -> Self {
+        if k >= Self::MAX_K {
+            u128::MAX
+        } else {
+            (1 << (2 * k as u32)) - 1
+        }
+    }
+
+    #[inline(always)]
+    fn to_u128(self) -> u128 {
+        self
+    }
+
+    #[inline(always)]
+    fn from_u128(value: u128) -> Self {
+        value
+    }
+}
+
+/// Iterate over canonical kmer of a sequence
+pub struct Canonical<'a, K> {
+    seq: &'a [u8],
+    k: u8,
+    pos: usize,
+    forward: K,
+    reverse: K,
+}
+
+impl<'a, K> Canonical<'a, K>
+where
+    K: Kmer,
+{
+    /// Create a new iterator, sequence shorter than k produce no kmer, k must be greater than 0
+    pub fn new(seq: &'a [u8], k: u8) -> error::Result<Self> {
+        if k == 0 {
+            return Err(error::Error::KmerSizeNull.into());
+        }
+
+        let mut tokenizer = Self {
+            seq,
+            k,
+            pos: 0,
+            forward: K::from_nuc(0),
+            reverse: K::from_nuc(0),
+        };
+
+        while tokenizer.pos < std::cmp::min(seq.len(), (k as usize).saturating_sub(1)) {
+            tokenizer.push();
+        }
+
+        Ok(tokenizer)
+    }
+
+    fn push(&mut self) {
+        let nuc = cocktail::kmer::nuc2bit(self.seq[self.pos]);
+
+        self.forward = ((self.forward << 2) & K::mask(self.k)) | K::from_nuc(nuc);
+        self.reverse =
+            (self.reverse >> 2) | (K::from_nuc(nuc ^ 0b10) << (2 * (self.k as usize - 1)));
+
+        self.pos += 1;
+    }
+}
+
+impl<'a, K> Iterator for Canonical<'a, K>
+where
+    K: Kmer,
+{
+    type Item = K;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.pos >= self.seq.len() {
+            return None;
+        }
+
+        self.push();
+
+        Some(std::cmp::min(self.forward, self.reverse))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SEQ: &[u8] = b"ACGTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTGACGACTGTAAAGCGAGTGGCCGTGGAATTTCAAGCTCAATTAGCCGAACCAATCCGCCTA";
+
+    #[test]
+    fn same_as_u64() {
+        for k in [5, 11, 31] {
+            let narrow: Vec<u128> = cocktail::tokenizer::Tokenizer::new(SEQ, k)
+                .map(|kmer| cocktail::kmer::revcomp(kmer, k) as u128)
+                .collect();
+            let wide: Vec<u128> = SEQ
+                .windows(k as usize)
+                .map(|window| u128::seq2bit(window).revcomp(k))
+                .collect();
+
+            assert_eq!(narrow, wide);
+        }
+
+        assert_eq!(u128::mask(5), u64::mask(5) as u128);
+        assert_eq!(u64::mask(32), u64::MAX);
+    }
+
+    #[test]
+    fn wide() {
+        let k = 51;
+        let kmer = u128::seq2bit(&SEQ[..k as usize]);
+
+        assert_eq!(kmer.kmer2seq(k).as_bytes(), &SEQ[..k as usize]);
+        assert_eq!(kmer.revcomp(k).revcomp(k), kmer);
+        assert_eq!(kmer.canonical(k), kmer.revcomp(k).canonical(k));
+        assert_eq!(kmer.canonical(k), std::cmp::min(kmer, kmer.revcomp(k)));
+        assert_eq!(kmer & u128::mask(k), kmer);
+        assert_eq!(u128::mask(64), u128::MAX);
+
+        let canonicals: Vec<u128> = Canonical::new(SEQ, k).unwrap().collect();
+        assert_eq!(canonicals.len(), SEQ.len() - k as usize + 1);
+        for (i, cano) in canonicals.iter().enumerate() {
+            assert_eq!(*cano, u128::seq2bit(&SEQ[i..i + k as usize]).canonical(k));
+        }
+    }
+
+    #[test]
+    fn null_kmer_size() {
+        assert!(Canonical::<u64>::new(SEQ, 0).is_err());
+        assert_eq!(Canonical::<u64>::new(SEQ, 1).unwrap().count(), SEQ.len());
+    }
+}
@@ -10,205 +10,1111 @@
 pub mod cli;
 pub mod correct;
 pub mod error;
+pub mod filter;
+pub mod kmer;
+pub mod parse;
+pub mod report;
 pub mod set;
+pub mod spectrum;
+pub mod stats;
+pub mod trim;
 
 /* crate use */
-#[cfg(feature = "parallel")]
-use rayon::prelude::*;
 
 /* local use */
 
-#[cfg(not(feature = "parallel"))]
-pub fn run_correction<'a>(
-    inputs: &mut [Box<dyn std::io::BufRead>],
-    outputs: &mut [Box<dyn std::io::Write>],
-    methods: Vec<Box<dyn correct::Corrector + Sync + Send + 'a>>,
-    two_side: bool,
-    _record_buffer_len: u64,
-) -> error::Result<()> {
-    for (input, output) in inputs.iter_mut().zip(outputs.iter_mut()) {
-        let mut reader = noodles::fasta::Reader::new(input);
-        let mut writer = noodles::fasta::Writer::new(output);
-
-        let mut records = reader.records();
-
-        while let Some(Ok(record)) = records.next() {
-            log::debug!(
-                "begin correct read {} {}",
-                String::from_utf8(record.name().to_vec()).unwrap(),
-                record.sequence().len()
-            );
-            let seq = record.sequence();
+/// Sequence record that br can correct
+pub trait SeqRecord: Sized {
+    /// Get name of record
+    fn name(&self) -> &[u8];
+
+    /// Get sequence of record
+    fn sequence(&self) -> &[u8];
+
+    /// Get quality of record, None if record have no quality
+    fn quality(&self) -> Option<&[u8]>;
+
+    /// Build a new record with same definition but a new sequence and quality
+    fn corrected(&self, seq: Vec<u8>, qual: Vec<u8>) -> Self;
+
+    /// Build a new record with a region of sequence, index is add at end of name
+    fn sub(&self, region: std::ops::Range<usize>, index: Option<usize>) -> Self;
+
+    /// Build a new record with annotation add at end of description
+    fn annotate(&self, annotation: &str) -> Self;
+}
+
+/// Add annotation at end of description
+fn annotate_description(description: &[u8], annotation: &str) -> Vec<u8> {
+    let mut description = description.to_vec();
+    if !description.is_empty() {
+        description.push(b' ');
+    }
+    description.extend_from_slice(annotation.as_bytes());
+
+    description
+}
+
+/// Add index at end of read name
+fn sub_name(name: &[u8], index: Option<usize>) -> Vec<u8> {
+    let mut name = name.to_vec();
+    if let Some(index) = index {
+        name.extend_from_slice(format!("/{}", index).as_bytes());
+    }
+
+    name
+}
+
+impl SeqRecord for noodles::fasta::Record {
+    fn name(&self) -> &[u8] {
+        noodles::fasta::Record::name(self)
+    }
+
+    fn sequence(&self) -> &[u8] {
+        noodles::fasta::Record::sequence(self).as_ref()
+    }
+
+    fn quality(&self) -> Option<&[u8]> {
+        None
+    }
+
+    fn corrected(&self, seq: Vec<u8>, _qual: Vec<u8>) -> Self {
+        noodles::fasta::Record::new(self.definition().clone(), seq.into())
+    }
+
+    fn sub(&self, region: std::ops::Range<usize>, index: Option<usize>) -> Self {
+        noodles::fasta::Record::new(
+            noodles::fasta::record::Definition::new(
+                sub_name(SeqRecord::name(self), index),
+                self.description().map(|d| d.to_vec()),
+            ),
+            SeqRecord::sequence(self)[region].to_vec().into(),
+        )
+    }
+
+    fn annotate(&self, annotation: &str) -> Self {
+        noodles::fasta::Record::new(
+            noodles::fasta::record::Definition::new(
+                SeqRecord::name(self).to_vec(),
+                Some(annotate_description(
+                    self.description().unwrap_or_default(),
+                    annotation,
+                )),
+            ),
+            self.sequence().clone(),
+        )
+    }
+}
+
+#[cfg(feature = "fastq")]
+impl SeqRecord for noodles::fastq::Record {
+    fn name(&self) -> &[u8] {
+        noodles::fastq::Record::name(self)
+    }
+
+    fn sequence(&self) -> &[u8] {
+        noodles::fastq::Record::sequence(self)
+    }
+
+    fn quality(&self) -> Option<&[u8]> {
+        Some(self.quality_scores())
+    }
+
+    fn corrected(&self, seq: Vec<u8>, qual: Vec<u8>) -> Self {
+        noodles::fastq::Record::new(self.definition().clone(), seq, qual)
+    }
+
+    fn sub(&self, region: std::ops::Range<usize>, index: Option<usize>) -> Self {
+        noodles::fastq::Record::new(
+            noodles::fastq::record::Definition::new(
+                sub_name(SeqRecord::name(self), index),
+                self.description().to_vec(),
+            ),
+            SeqRecord::sequence(self)[region.clone()].to_vec(),
+            self.quality_scores()[region].to_vec(),
+        )
+    }
+
+    fn annotate(&self, annotation: &str) -> Self {
+        noodles::fastq::Record::new(
+            noodles::fastq::record::Definition::new(
+                SeqRecord::name(self).to_vec(),
+                annotate_description(self.description(), annotation),
+            ),
+            SeqRecord::sequence(self).to_vec(),
+            self.quality_scores().to_vec(),
+        )
+    }
+}
+
+/// Sequence file format
+#[derive(
+    std::fmt::Debug, std::clone::Clone, std::marker::Copy, std::cmp::PartialEq, std::cmp::Eq,
+)]
+pub enum Format {
+    /// Fasta format
+    Fasta,
+
+    /// Fastq format
+    Fastq,
+}
+
+/// Detect format of input by look first byte, input isn't consume
+pub fn detect_format(input: &mut dyn std::io::BufRead) -> error::Result<Format> {
+    match input.fill_buf()?.first() {
+        Some(b'@') => Ok(Format::Fastq),
+        _ => Ok(Format::Fasta),
+    }
+}
 
-            let mut correct = seq.as_ref().to_vec();
-            methods
+/// Write reads of input in fasta, fasta input is copy as is
+pub fn reads2fasta<W>(input: &mut dyn std::io::BufRead, mut output: W) -> error::Result<()>
+where
+    W: std::io::Write,
+{
+    match detect_format(input)? {
+        Format::Fasta => {
+            std::io::copy(input, &mut output)?;
+        }
+        #[cfg(feature = "fastq")]
+        Format::Fastq => {
+            let mut reader = noodles::fastq::Reader::new(input);
+
+            for result in reader.records() {
+                let record = result?;
+
+                output.write_all(b">")?;
+                output.write_all(SeqRecord::name(&record))?;
+                output.write_all(b"\n")?;
+                output.write_all(SeqRecord::sequence(&record))?;
+                output.write_all(b"\n")?;
+            }
+        }
+        #[cfg(not(feature = "fastq"))]
+        Format::Fastq => return Err(error::Error::FastqRequireFeature.into()),
+    }
+
+    Ok(())
+}
+
+/// Result of correction of one record
+pub struct Corrected<R> {
+    /// Corrected record
+    pub record: R,
+
+    /// Length of original read
+    pub length: usize,
+
+    /// Edits apply on read, position is in original read
+    pub edits: Vec<correct::Edit>,
+
+    /// Correction attempts, position is in original read
+    pub events: Vec<correct::Event>,
+
+    /// Region of corrected read keep by trimming, whole read is keep if None
+    pub regions: Option<Vec<std::ops::Range<usize>>>,
+
+    /// Ratio of solid kmer before and after correction, None if it isn't compute
+    pub solid_ratio: Option<(f64, f64)>,
+
+    /// False if read is drop by solid ratio filter
+    pub keep: bool,
+}
+
+impl<R> Corrected<R>
+where
+    R: SeqRecord + std::clone::Clone,
+{
+    /// True if trimming drop whole read because it didn't contains any solid kmer
+    pub fn trimmed_out(&self) -> bool {
+        self.regions
+            .as_ref()
+            .is_some_and(|regions| regions.is_empty())
+    }
+
+    /// Get records to write, region are number from 1 if read is split
+    pub fn records(&self) -> Vec<std::borrow::Cow<'_, R>> {
+        if !self.keep {
+            return Vec::new();
+        }
+
+        match &self.regions {
+            None => vec![std::borrow::Cow::Borrowed(&self.record)],
+            Some(regions) if regions.len() == 1 => {
+                vec![std::borrow::Cow::Owned(
+                    self.record.sub(regions[0].clone(), None),
+                )]
+            }
+            Some(regions) => regions
                 .iter()
-                .for_each(|x| correct = x.correct(correct.as_slice()));
+                .enumerate()
+                .map(|(i, region)| {
+                    std::borrow::Cow::Owned(self.record.sub(region.clone(), Some(i + 1)))
+                })
+                .collect(),
+        }
+    }
+}
 
-            if !two_side {
-                correct.reverse();
-                methods
-                    .iter()
-                    .for_each(|x| correct = x.correct(correct.as_slice()));
+/// Read under correction
+struct Read {
+    seq: Vec<u8>,
+    qual: Option<Vec<u8>>,
+    origin: Vec<usize>,
+    len: usize,
+    edits: Vec<correct::Edit>,
+    events: Vec<correct::Event>,
+}
+
+impl Read {
+    /// Reverse complement read, quality and origin are reverse
+    fn reverse_complement(&mut self) {
+        correct::revcomp(&mut self.seq);
+        if let Some(qual) = self.qual.as_mut() {
+            qual.reverse();
+        }
+        self.origin.reverse();
+    }
+
+    fn correct<K>(
+        &mut self,
+        method: &dyn correct::Corrector<K>,
+        ins_qual: u8,
+        ambiguous: correct::AmbiguousPolicy,
+        reverse: bool,
+    ) where
+        K: kmer::Kmer,
+    {
+        let (seq, events) = method.correct_ambiguous(&self.seq, ambiguous);
+        let edits: Vec<correct::Edit> = events
+            .iter()
+            .filter_map(|event| event.to_edit(&self.seq))
+            .collect();
 
-                correct.reverse();
+        for mut edit in edits.iter().cloned() {
+            edit.pos = self.original_pos(edit.pos, edit.original.len(), reverse);
+            if reverse {
+                correct::revcomp(&mut edit.original);
+                correct::revcomp(&mut edit.replacement);
             }
 
-            writer.write_record(&noodles::fasta::Record::new(
-                record.definition().clone(),
-                correct.into(),
-            ))?;
-            log::debug!(
-                "end correct read {}",
-                String::from_utf8(record.name().to_vec()).unwrap()
-            );
+            self.edits.push(edit);
         }
+
+        for mut event in events {
+            event.pos = self.original_pos(event.pos, 1, reverse);
+
+            self.events.push(event);
+        }
+
+        let origin = &self.origin;
+        let read_len = self.len;
+        self.origin = correct::realign(origin, &edits, |edit| {
+            origin.get(edit.pos).copied().unwrap_or(read_len)
+        });
+        if let Some(qual) = self.qual.as_ref() {
+            self.qual = Some(correct::realign(qual, &edits, |_| ins_qual));
+        }
+        self.seq = seq;
     }
 
-    Ok(())
+    /// Convert position of a region in current read in position in original read
+    fn original_pos(&self, pos: usize, len: usize, reverse: bool) -> usize {
+        if !reverse {
+            self.origin.get(pos).copied().unwrap_or(self.len)
+        } else if len == 0 {
+            // insertion in reversed read is after base in forward read
+            self.origin.get(pos).map_or(0, |pos| pos + 1)
+        } else {
+            self.origin[pos..std::cmp::min(pos + len, self.origin.len())]
+                .iter()
+                .copied()
+                .min()
+                .unwrap_or(0)
+        }
+    }
+}
+
+#[cfg(not(feature = "parallel"))]
+/// Records are read, correct and write one by one by calling thread
+fn run_correction<K, R, W>(
+    pipeline: &CorrectionPipeline<K>,
+    names: &[String],
+    inputs: &mut [R],
+    outputs: &mut [W],
+    mut report: Option<&mut report::Report>,
+) -> error::Result<stats::Stats>
+where
+    K: kmer::Kmer,
+    R: std::io::BufRead,
+    W: std::io::Write,
+{
+    if inputs.len() != outputs.len() {
+        return Err(error::Error::InputOutputMismatch {
+            inputs: inputs.len(),
+            outputs: outputs.len(),
+        }
+        .into());
+    }
+
+    let mut stats = stats::Stats::default();
+
+    for (i, (input, output)) in inputs.iter_mut().zip(outputs.iter_mut()).enumerate() {
+        let name = names
+            .get(i)
+            .cloned()
+            .unwrap_or_else(|| format!("input {}", i + 1));
+        let format = detect_format(input)?;
+        let (input, offset) = parse::CountReader::new(input);
+
+        match format {
+            Format::Fasta => {
+                let mut reader = noodles::fasta::Reader::new(input);
+                let mut writer = noodles::fasta::Writer::new(output);
+
+                stats.merge(correct_records(
+                    pipeline,
+                    parse::Records::new(reader.records(), name, offset, pipeline.skip_invalid),
+                    |corrected| {
+                        for record in corrected.records() {
+                            writer.write_record(&record)?;
+                        }
+                        if let Some(report) = report.as_mut() {
+                            report.write(corrected.record.name(), &corrected.edits)?;
+                        }
+                        Ok(())
+                    },
+                )?);
+            }
+            #[cfg(feature = "fastq")]
+            Format::Fastq => {
+                let mut reader = noodles::fastq::Reader::new(input);
+                let mut writer = noodles::fastq::Writer::new(output);
+
+                stats.merge(correct_records(
+                    pipeline,
+                    parse::Records::new(
+                        reader.records().map(parse::check_quality),
+                        name,
+                        offset,
+                        pipeline.skip_invalid,
+                    ),
+                    |corrected| {
+                        for record in corrected.records() {
+                            writer.write_record(&record)?;
+                        }
+                        if let Some(report) = report.as_mut() {
+                            report.write(corrected.record.name(), &corrected.edits)?;
+                        }
+                        Ok(())
+                    },
+                )?);
+            }
+            #[cfg(not(feature = "fastq"))]
+            Format::Fastq => return Err(error::Error::FastqRequireFeature.into()),
+        }
+    }
+
+    Ok(stats)
 }
 
+#[cfg(not(feature = "parallel"))]
+fn correct_records<'a, I, R, W, K>(
+    pipeline: &CorrectionPipeline<'a, K>,
+    records: I,
+    mut write: W,
+) -> error::Result<stats::Stats>
+where
+    I: Iterator<Item = error::Result<R>>,
+    R: SeqRecord,
+    W: FnMut(&Corrected<R>) -> std::io::Result<()>,
+    K: kmer::Kmer,
+{
+    let mut stats = stats::Stats::default();
+
+    for record in records {
+        let corrected = pipeline.correct_record(&record?);
+
+        stats.add_read(corrected.length, &corrected.edits, &corrected.events);
+        if corrected.trimmed_out() {
+            stats.trimmed_reads += 1;
+        }
+        write(&corrected)?;
+    }
+
+    Ok(stats)
+}
+
+#[cfg(feature = "parallel")]
+/// Maximal number of bases in a batch of records send to a correction worker
+const BATCH_BASES: usize = 1 << 22;
+
 #[cfg(feature = "parallel")]
-pub fn run_correction<'a>(
-    inputs: &mut [Box<dyn std::io::BufRead>],
-    outputs: &mut [Box<dyn std::io::Write>],
-    methods: Vec<Box<dyn correct::Corrector + Sync + Send + 'a>>,
-    two_side: bool,
-    record_buffer_len: u64,
-) -> error::Result<()> {
-    for (input, output) in inputs.iter_mut().zip(outputs.iter_mut()) {
-        let mut reader = noodles::fasta::Reader::new(input);
-        let mut writer = noodles::fasta::Writer::new(output);
-
-        let mut iter = reader.records();
-        let mut records = Vec::with_capacity(record_buffer_len as usize);
-        let mut corrected: Vec<error::Result<noodles::fasta::Record>>;
-
-        let mut end = true;
-        while end {
-            log::info!("Start populate buffer");
-            end = populate_buffer(&mut iter, &mut records, 8192);
-            log::info!("End populate buffer {}", records.len());
-
-            corrected = records
-                .drain(..)
-                .par_bridge()
-                .map(|record| {
-                    log::debug!(
-                        "begin correct read {} {}",
-                        String::from_utf8(record.name().to_vec()).unwrap(),
-                        record.sequence().len()
-                    );
-
-                    let seq = record.sequence();
-
-                    let mut correct = seq.as_ref().to_vec();
-                    methods
-                        .iter()
-                        .for_each(|x| correct = x.correct(correct.as_slice()));
-
-                    if !two_side {
-                        correct.reverse();
-                        methods
-                            .iter()
-                            .for_each(|x| correct = x.correct(correct.as_slice()));
-
-                        correct.reverse();
+/// Correct records of each input with rayon workers, order of each output is order of its input
+///
+/// Calling thread read batches of records, workers correct them and a writer thread write them in order.
+/// At most record_buffer records, and a bounded number of bases, of at most concurrent_files inputs are in flight.
+fn run_correction<K, R, W>(
+    pipeline: &CorrectionPipeline<K>,
+    names: &[String],
+    inputs: &mut [R],
+    outputs: &mut [W],
+    report: Option<&mut report::Report>,
+) -> error::Result<stats::Stats>
+where
+    K: kmer::Kmer,
+    R: std::io::BufRead,
+    W: std::io::Write + std::marker::Send,
+{
+    if inputs.len() != outputs.len() {
+        return Err(error::Error::InputOutputMismatch {
+            inputs: inputs.len(),
+            outputs: outputs.len(),
+        }
+        .into());
+    }
+
+    let (batch_sender, batch_receiver) = std::sync::mpsc::channel();
+    let (written_sender, written_receiver) = std::sync::mpsc::channel();
+
+    std::thread::scope(|threads| {
+        let writer =
+            threads.spawn(move || write_batches(batch_receiver, written_sender, outputs, report));
+
+        let read = rayon::in_place_scope(|scope| -> error::Result<()> {
+            // scheduler is drop at end of reading, writer stop after last batch
+            let mut scheduler = Scheduler::new(
+                batch_sender,
+                written_receiver,
+                pipeline.record_buffer as usize,
+                pipeline.concurrent_files,
+            );
+
+            for (i, input) in inputs.iter_mut().enumerate() {
+                let name = names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| format!("input {}", i + 1));
+                let format = detect_format(input)?;
+                let (input, offset) = parse::CountReader::new(input);
+
+                match format {
+                    Format::Fasta => {
+                        let mut reader = noodles::fasta::Reader::new(input);
+
+                        scheduler.dispatch(
+                            scope,
+                            i,
+                            parse::Records::new(
+                                reader.records(),
+                                name,
+                                offset,
+                                pipeline.skip_invalid,
+                            ),
+                            pipeline,
+                            |data, record| noodles::fasta::Writer::new(data).write_record(record),
+                        )?;
                     }
+                    #[cfg(feature = "fastq")]
+                    Format::Fastq => {
+                        let mut reader = noodles::fastq::Reader::new(input);
 
-                    log::debug!(
-                        "end correct read {}",
-                        String::from_utf8(record.name().to_vec()).unwrap()
-                    );
-                    Ok(noodles::fasta::Record::new(
-                        record.definition().clone(),
-                        correct.into(),
-                    ))
-                })
-                .collect();
+                        scheduler.dispatch(
+                            scope,
+                            i,
+                            parse::Records::new(
+                                reader.records().map(parse::check_quality),
+                                name,
+                                offset,
+                                pipeline.skip_invalid,
+                            ),
+                            pipeline,
+                            |data, record| noodles::fastq::Writer::new(data).write_record(record),
+                        )?;
+                    }
+                    #[cfg(not(feature = "fastq"))]
+                    Format::Fastq => return Err(error::Error::FastqRequireFeature.into()),
+                }
+            }
+
+            Ok(())
+        });
+
+        let write = writer
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic));
+
+        // a writer error stop reading, so it's the first error
+        match (read, write) {
+            (_, Err(error)) => Err(error),
+            (Err(error), Ok(_)) => Err(error),
+            (Ok(()), Ok(stats)) => Ok(stats),
+        }
+    })
+}
 
-            for corr in corrected {
-                writer.write_record(&corr?)?
+#[cfg(feature = "parallel")]
+/// Batch of records corrected and serialized by a worker
+struct Batch {
+    /// Index of output
+    output: usize,
+    /// Serialized corrected records
+    data: Vec<u8>,
+    /// Name and edits of each record, use by report
+    edits: Vec<(Vec<u8>, Vec<correct::Edit>)>,
+    /// Statistics of batch
+    stats: stats::Stats,
+}
+
+#[cfg(feature = "parallel")]
+/// Receiver of a batch result
+type BatchReceiver = std::sync::mpsc::Receiver<std::io::Result<Batch>>;
+
+#[cfg(feature = "parallel")]
+/// Dispatch batch of records to rayon workers and send their result receiver in order to writer
+struct Scheduler {
+    /// Send result receiver of each batch to writer, in dispatch order
+    batches: std::sync::mpsc::Sender<BatchReceiver>,
+    /// Writer send a message each time a batch is write
+    written: std::sync::mpsc::Receiver<()>,
+    /// Output index, number of records and number of bases of each batch in flight
+    pending: std::collections::VecDeque<(usize, usize, usize)>,
+    in_flight: usize,
+    bases_in_flight: usize,
+    record_buffer: usize,
+    bases_buffer: usize,
+    batch_len: usize,
+    concurrent_files: usize,
+}
+
+#[cfg(feature = "parallel")]
+impl Scheduler {
+    fn new(
+        batches: std::sync::mpsc::Sender<BatchReceiver>,
+        written: std::sync::mpsc::Receiver<()>,
+        record_buffer: usize,
+        concurrent_files: usize,
+    ) -> Self {
+        let record_buffer = record_buffer.max(1);
+
+        Self {
+            batches,
+            written,
+            pending: std::collections::VecDeque::new(),
+            in_flight: 0,
+            bases_in_flight: 0,
+            record_buffer,
+            bases_buffer: 2 * rayon::current_num_threads() * BATCH_BASES,
+            batch_len: (record_buffer / (2 * rayon::current_num_threads())).max(1),
+            concurrent_files: concurrent_files.max(1),
+        }
+    }
+
+    /// Read records by batch and send them to workers
+    fn dispatch<'s, 'a, I, R, K, S>(
+        &mut self,
+        scope: &rayon::Scope<'s>,
+        output: usize,
+        mut records: I,
+        pipeline: &'s CorrectionPipeline<'a, K>,
+        serialize: S,
+    ) -> error::Result<()>
+    where
+        I: Iterator<Item = error::Result<R>>,
+        R: SeqRecord + std::clone::Clone + Send + 's,
+        K: kmer::Kmer,
+        S: Fn(&mut Vec<u8>, &R) -> std::io::Result<()> + Copy + Send + 's,
+    {
+        // wait end of oldest inputs if too many inputs are in flight
+        while self
+            .pending
+            .front()
+            .is_some_and(|(front, _, _)| output - front >= self.concurrent_files)
+        {
+            self.wait_written()?;
+        }
+
+        let mut end = false;
+        while !end {
+            let mut batch = Vec::with_capacity(self.batch_len);
+            let mut bases = 0;
+            while batch.len() < self.batch_len && bases < BATCH_BASES {
+                match records.next() {
+                    Some(record) => {
+                        let record = record?;
+                        bases += record.sequence().len();
+                        batch.push(record);
+                    }
+                    None => {
+                        end = true;
+                        break;
+                    }
+                }
+            }
+
+            if !batch.is_empty() {
+                let len = batch.len();
+
+                // an input without batch in flight can always send one batch, so progress is guaranteed
+                while (self.in_flight + len > self.record_buffer
+                    || self.bases_in_flight + bases > self.bases_buffer)
+                    && !self.pending.is_empty()
+                {
+                    self.wait_written()?;
+                }
+
+                let (sender, receiver) = std::sync::mpsc::sync_channel(1);
+
+                scope.spawn(move |_| {
+                    // receiver is drop only if writing failed
+                    let _ = sender.send(correct_batch(output, batch, pipeline, serialize));
+                });
+
+                self.batches
+                    .send(receiver)
+                    .map_err(|_| error::Error::WriterStop)?;
+                self.in_flight += len;
+                self.bases_in_flight += bases;
+                self.pending.push_back((output, len, bases));
             }
 
-            records.clear();
+            // release batch already write without wait
+            while self.written.try_recv().is_ok() {
+                self.release();
+            }
         }
+
+        Ok(())
     }
 
-    Ok(())
+    /// Wait writer write oldest batch in flight
+    fn wait_written(&mut self) -> error::Result<()> {
+        wait_message(&self.written).map_err(|_| error::Error::WriterStop)?;
+        self.release();
+
+        Ok(())
+    }
+
+    /// Remove oldest batch in flight
+    fn release(&mut self) {
+        if let Some((_, len, bases)) = self.pending.pop_front() {
+            self.in_flight -= len;
+            self.bases_in_flight -= bases;
+        }
+    }
+}
+
+#[cfg(feature = "parallel")]
+/// Write batch in order of their receiver, send a message after each batch write and return statistics of all batches, stop when scheduler is drop
+fn write_batches<W>(
+    batches: std::sync::mpsc::Receiver<BatchReceiver>,
+    written: std::sync::mpsc::Sender<()>,
+    outputs: &mut [W],
+    mut report: Option<&mut report::Report>,
+) -> error::Result<stats::Stats>
+where
+    W: std::io::Write,
+{
+    let mut stats = stats::Stats::default();
+
+    for receiver in batches {
+        let batch = receiver
+            .recv()
+            .map_err(|_| error::Error::CorrectionWorkerStop)??;
+
+        outputs[batch.output].write_all(&batch.data)?;
+        if let Some(report) = report.as_mut() {
+            for (name, edits) in batch.edits.iter() {
+                report.write(name, edits)?;
+            }
+        }
+        stats.merge(batch.stats);
+
+        // scheduler is drop before writer if reading failed
+        let _ = written.send(());
+    }
+
+    Ok(stats)
 }
 
-pub fn build_methods<'a>(
-    params: Vec<cli::CorrectionMethod>,
-    solid: &'a set::BoxKmerSet,
+#[cfg(feature = "parallel")]
+/// Correct a batch of records and serialize corrected records
+fn correct_batch<R, K, S>(
+    output: usize,
+    records: Vec<R>,
+    pipeline: &CorrectionPipeline<K>,
+    serialize: S,
+) -> std::io::Result<Batch>
+where
+    R: SeqRecord + std::clone::Clone,
+    K: kmer::Kmer,
+    S: Fn(&mut Vec<u8>, &R) -> std::io::Result<()>,
+{
+    let mut batch = Batch {
+        output,
+        data: Vec::new(),
+        edits: Vec::with_capacity(records.len()),
+        stats: stats::Stats::default(),
+    };
+
+    for record in records.iter() {
+        let corrected = pipeline.correct_record(record);
+
+        batch
+            .stats
+            .add_read(corrected.length, &corrected.edits, &corrected.events);
+        if corrected.trimmed_out() {
+            batch.stats.trimmed_reads += 1;
+        }
+        for record in corrected.records() {
+            serialize(&mut batch.data, &record)?;
+        }
+        batch
+            .edits
+            .push((corrected.record.name().to_vec(), corrected.edits));
+    }
+
+    Ok(batch)
+}
+
+#[cfg(feature = "parallel")]
+/// Wait a message, if calling thread is a rayon worker it execute pending job instead of block, return an error if sender is drop
+fn wait_message<T>(
+    receiver: &std::sync::mpsc::Receiver<T>,
+) -> std::result::Result<T, std::sync::mpsc::RecvError> {
+    if rayon::current_thread_index().is_none() {
+        return receiver.recv();
+    }
+
+    loop {
+        match receiver.try_recv() {
+            Ok(message) => return Ok(message),
+            Err(std::sync::mpsc::TryRecvError::Empty) => {
+                if rayon::yield_now() != Some(rayon::Yield::Executed) {
+                    std::thread::yield_now()
+                }
+            }
+            Err(std::sync::mpsc::TryRecvError::Disconnected) => {
+                return Err(std::sync::mpsc::RecvError)
+            }
+        }
+    }
+}
+
+pub fn build_methods<'a, K>(
+    params: Vec<correct::Method>,
+    solid: &'a set::BoxKmerSet<K>,
     confirm: u8,
     max_search: u8,
-) -> Vec<Box<dyn correct::Corrector + Sync + Send + 'a>> {
-    let mut methods: Vec<Box<dyn correct::Corrector + Sync + Send + 'a>> = Vec::new();
+) -> Vec<Box<dyn correct::Corrector<K> + Sync + Send + 'a>>
+where
+    K: kmer::Kmer,
+{
+    let mut methods: Vec<Box<dyn correct::Corrector<K> + Sync + Send + 'a>> = Vec::new();
 
     for method in params {
-        match method {
-            cli::CorrectionMethod::One => methods.push(Box::new(correct::One::new(solid, confirm))),
-            cli::CorrectionMethod::Two => methods.push(Box::new(correct::Two::new(solid, confirm))),
-            cli::CorrectionMethod::Graph => methods.push(Box::new(correct::Graph::new(solid))),
-            cli::CorrectionMethod::Greedy => {
-                methods.push(Box::new(correct::Greedy::new(solid, max_search, confirm)))
+        methods.push(method.config(confirm, max_search).build(solid));
+    }
+
+    methods
+}
+
+/// Build correction methods against several solid set, each method use solid set with kmer size assign to it, or first solid set
+pub fn build_methods_multi_k<'a, K>(
+    configs: Vec<correct::MethodConfig>,
+    solids: &'a [set::BoxKmerSet<K>],
+    kmer_sizes: &[correct::MethodKmerSize],
+) -> error::Result<Vec<Box<dyn correct::Corrector<K> + Sync + Send + 'a>>>
+where
+    K: kmer::Kmer,
+{
+    let mut methods = Vec::new();
+
+    for config in configs {
+        let solid = match kmer_sizes
+            .iter()
+            .rev()
+            .find(|x| x.method == config.method())
+        {
+            Some(assign) => solids
+                .iter()
+                .find(|solid| solid.k() == assign.kmer_size)
+                .ok_or(error::Error::NoSolidWithKmerSize(assign.kmer_size))?,
+            None => solids.first().ok_or(error::Error::NoSolidSet)?,
+        };
+
+        methods.push(config.build(solid));
+    }
+
+    Ok(methods)
+}
+
+/// Correction pipeline, a set of correctors and parameters of correction
+pub struct CorrectionPipeline<'a, K = u64>
+where
+    K: kmer::Kmer,
+{
+    solid: &'a set::BoxKmerSet<'a, K>,
+    methods: Vec<Box<dyn correct::Corrector<K> + Sync + Send + 'a>>,
+    forward_only: bool,
+    // record buffer and concurrent files are only use by parallel correction
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    record_buffer: u64,
+    insertion_quality: u8,
+    ambiguous: correct::AmbiguousPolicy,
+    trim: Option<trim::Trim>,
+    ratio: Option<filter::SolidRatio>,
+    skip_invalid: bool,
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    concurrent_files: usize,
+}
+
+impl<'a, K> CorrectionPipeline<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a pipeline builder, methods without solid set use this one
+    pub fn builder(solid: &'a set::BoxKmerSet<'a, K>) -> CorrectionPipelineBuilder<'a, K> {
+        CorrectionPipelineBuilder::new(solid)
+    }
+
+    /// Correct one record with all methods
+    pub fn correct_record<R>(&self, record: &R) -> Corrected<R>
+    where
+        R: SeqRecord,
+    {
+        log::debug!(
+            "begin correct read {} {}",
+            String::from_utf8_lossy(record.name()),
+            record.sequence().len()
+        );
+
+        let ins_qual = self.insertion_quality.saturating_add(b'!');
+
+        let mut read = Read {
+            seq: record.sequence().to_vec(),
+            qual: record.quality().map(|q| q.to_vec()),
+            origin: (0..record.sequence().len()).collect(),
+            len: record.sequence().len(),
+            edits: Vec::new(),
+            events: Vec::new(),
+        };
+
+        for method in &self.methods {
+            read.correct(method.as_ref(), ins_qual, self.ambiguous, false);
+        }
+
+        if !self.forward_only {
+            // forward pass copy first kmer as is, backward pass on reverse complement can correct it
+            read.reverse_complement();
+
+            for method in &self.methods {
+                read.correct(method.as_ref(), ins_qual, self.ambiguous, true);
             }
-            cli::CorrectionMethod::GapSize => {
-                methods.push(Box::new(correct::GapSize::new(solid, confirm)))
+
+            read.reverse_complement();
+        }
+
+        read.edits.sort_by_key(|edit| edit.pos);
+
+        // trimming and solid ratio use base solid set, result didn't depend on methods order
+        let regions = self.trim.map(|trim| trim.regions(&read.seq, self.solid));
+
+        let solid_ratio = self.ratio.map(|_| {
+            (
+                filter::solid_ratio(record.sequence(), self.solid),
+                filter::solid_ratio(&read.seq, self.solid),
+            )
+        });
+        let keep = match (self.ratio, solid_ratio) {
+            (Some(ratio), Some((_, after))) => ratio.keep(after),
+            _ => true,
+        };
+
+        let mut corrected = record.corrected(read.seq, read.qual.unwrap_or_default());
+        if let (Some(ratio), Some((before, after))) = (self.ratio, solid_ratio) {
+            if ratio.annotate {
+                corrected = corrected.annotate(&filter::SolidRatio::annotation(before, after));
             }
         }
+
+        log::debug!(
+            "end correct read {}",
+            String::from_utf8_lossy(record.name())
+        );
+
+        Corrected {
+            record: corrected,
+            length: read.len,
+            edits: read.edits,
+            events: read.events,
+            regions,
+            solid_ratio,
+            keep,
+        }
     }
 
-    methods
+    /// Correct each record of an iterator
+    pub fn correct_iter<'b, I, R>(
+        &'b self,
+        records: I,
+    ) -> impl Iterator<Item = std::io::Result<Corrected<R>>> + 'b
+    where
+        I: Iterator<Item = std::io::Result<R>> + 'b,
+        R: SeqRecord + 'b,
+    {
+        records.map(move |record| record.map(|record| self.correct_record(&record)))
+    }
+
+    /// Correct each input in matching output, format of output is format of input, names of inputs are use in parsing error and can be empty
+    pub fn correct_files<R, W>(
+        &self,
+        names: &[String],
+        inputs: &mut [R],
+        outputs: &mut [W],
+        report: Option<&mut report::Report>,
+    ) -> error::Result<stats::Stats>
+    where
+        R: std::io::BufRead,
+        W: std::io::Write + std::marker::Send,
+    {
+        run_correction(self, names, inputs, outputs, report)
+    }
 }
 
-#[cfg(feature = "parallel")]
-/// Populate record buffer with content of iterator
-fn populate_buffer<R>(
-    iter: &mut noodles::fasta::reader::Records<'_, R>,
-    records: &mut Vec<noodles::fasta::Record>,
-    record_buffer: u64,
-) -> bool
+/// Builder of correction pipeline
+pub struct CorrectionPipelineBuilder<'a, K = u64>
 where
-    R: std::io::BufRead,
+    K: kmer::Kmer,
 {
-    records.clear();
+    solid: &'a set::BoxKmerSet<'a, K>,
+    pipeline: CorrectionPipeline<'a, K>,
+}
 
-    for i in 0..record_buffer {
-        if let Some(Ok(record)) = iter.next() {
-            records.push(record);
-        } else {
-            records.truncate(i as usize);
-            return false;
+impl<'a, K> CorrectionPipelineBuilder<'a, K>
+where
+    K: kmer::Kmer,
+{
+    /// Create a builder without correction method, two side correction and default parameters
+    pub fn new(solid: &'a set::BoxKmerSet<'a, K>) -> Self {
+        Self {
+            solid,
+            pipeline: CorrectionPipeline {
+                solid,
+                methods: Vec::new(),
+                forward_only: false,
+                record_buffer: 8192,
+                insertion_quality: 20,
+                ambiguous: correct::AmbiguousPolicy::Resolve,
+                trim: None,
+                ratio: None,
+                skip_invalid: false,
+                concurrent_files: 1,
+            },
         }
     }
 
-    true
+    /// Add a correction method on solid set of builder
+    pub fn method(self, config: correct::MethodConfig) -> Self {
+        let solid = self.solid;
+
+        self.method_with_set(config, solid)
+    }
+
+    /// Add a correction method on another solid set
+    pub fn method_with_set(
+        mut self,
+        config: correct::MethodConfig,
+        solid: &'a set::BoxKmerSet<'a, K>,
+    ) -> Self {
+        self.pipeline.methods.push(config.build(solid));
+        self
+    }
+
+    /// Add a corrector already build
+    pub fn corrector(
+        mut self,
+        corrector: Box<dyn correct::Corrector<K> + Sync + Send + 'a>,
+    ) -> Self {
+        self.pipeline.methods.push(corrector);
+        self
+    }
+
+    /// Correct only in forward direction if true, backward pass on reverse complement is skip
+    pub fn forward_only(mut self, forward_only: bool) -> Self {
+        self.pipeline.forward_only = forward_only;
+        self
+    }
+
+    /// Set number of record load in buffer
+    pub fn record_buffer(mut self, record_buffer: u64) -> Self {
+        self.pipeline.record_buffer = record_buffer;
+        self
+    }
+
+    /// Set phred quality of inserted base
+    pub fn insertion_quality(mut self, insertion_quality: u8) -> Self {
+        self.pipeline.insertion_quality = insertion_quality;
+        self
+    }
+
+    /// Set policy apply on non ACGT bases
+    pub fn ambiguous(mut self, ambiguous: correct::AmbiguousPolicy) -> Self {
+        self.pipeline.ambiguous = ambiguous;
+        self
+    }
+
+    /// Set trimming parameter
+    pub fn trim(mut self, trim: Option<trim::Trim>) -> Self {
+        self.pipeline.trim = trim;
+        self
+    }
+
+    /// Set solid ratio annotation and filter parameter
+    pub fn solid_ratio(mut self, ratio: Option<filter::SolidRatio>) -> Self {
+        self.pipeline.ratio = ratio;
+        self
+    }
+
+    /// Log and skip invalid records instead of stop on first one
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.pipeline.skip_invalid = skip_invalid;
+        self
+    }
+
+    /// Set number of inputs with records in correction at same time, only use with parallel feature
+    pub fn concurrent_files(mut self, concurrent_files: usize) -> Self {
+        self.pipeline.concurrent_files = concurrent_files;
+        self
+    }
+
+    /// Build pipeline
+    pub fn build(self) -> CorrectionPipeline<'a, K> {
+        self.pipeline
+    }
 }
 
 #[cfg(feature = "parallel")]
 /// Populate record buffer with content of iterator
-fn populate_bufferq<R>(
-    iter: &mut noodles::fastq::reader::Records<'_, R>,
-    records: &mut Vec<noodles::fastq::Record>,
+pub(crate) fn populate_buffer<I, R, E>(
+    iter: &mut I,
+    records: &mut Vec<R>,
     record_buffer: u64,
-) -> bool
+) -> error::Result<bool>
 where
-    R: std::io::BufRead,
+    I: Iterator<Item = std::result::Result<R, E>>,
+    E: std::convert::Into<anyhow::Error>,
 {
     records.clear();
 
-    for i in 0..record_buffer {
-        if let Some(Ok(record)) = iter.next() {
-            records.push(record);
-        } else {
-            records.truncate(i as usize);
-            return false;
+    for _ in 0..record_buffer {
+        match iter.next() {
+            Some(record) => records.push(record.map_err(|e| e.into())?),
+            None => return Ok(false),
         }
     }
 
-    true
+    Ok(true)
 }
 
 #[cfg(test)]
@@ -221,23 +1127,18 @@ mod tests {
 
         let set: set::BoxKmerSet = Box::new(set::Pcon::new(pcon::solid::Solid::new(5)));
 
-        let methods = build_methods(
-            vec![cli::CorrectionMethod::One, cli::CorrectionMethod::Two],
-            &set,
-            2,
-            5,
-        );
+        let methods = build_methods(vec![correct::Method::One, correct::Method::Two], &set, 2, 5);
 
         assert_eq!(methods.len(), 2);
 
         let methods = build_methods(
             vec![
-                cli::CorrectionMethod::One,
-                cli::CorrectionMethod::Two,
-                cli::CorrectionMethod::Graph,
-                cli::CorrectionMethod::Greedy,
-                cli::CorrectionMethod::GapSize,
-                cli::CorrectionMethod::GapSize,
+                correct::Method::One,
+                correct::Method::Two,
+                correct::Method::Graph,
+                correct::Method::Greedy,
+                correct::Method::GapSize,
+                correct::Method::GapSize,
             ],
             &set,
             2,
@@ -246,4 +1147,348 @@ mod tests {
 
         assert_eq!(methods.len(), 6);
     }
+
+    #[test]
+    fn methods_multi_k() {
+        let small = pcon::solid::Solid::new(5);
+        let large = pcon::solid::Solid::new(7);
+
+        let sets: Vec<set::BoxKmerSet> = vec![
+            Box::new(set::Pcon::new(small)),
+            Box::new(set::Pcon::new(large)),
+        ];
+
+        let methods = build_methods_multi_k(
+            vec![
+                correct::MethodConfig::GapSize { confirm: 2 },
+                correct::MethodConfig::Greedy {
+                    max_search: 5,
+                    confirm: 2,
+                },
+            ],
+            &sets,
+            &[correct::MethodKmerSize {
+                method: correct::Method::Greedy,
+                kmer_size: 7,
+            }],
+        )
+        .unwrap();
+
+        assert_eq!(
+            methods.iter().map(|m| m.k()).collect::<Vec<u8>>(),
+            vec![5, 7]
+        );
+
+        assert!(build_methods_multi_k(
+            vec![correct::MethodConfig::Greedy {
+                max_search: 5,
+                confirm: 2,
+            }],
+            &sets,
+            &[correct::MethodKmerSize {
+                method: correct::Method::Greedy,
+                kmer_size: 9,
+            }],
+        )
+        .is_err());
+
+        let empty: Vec<set::BoxKmerSet> = Vec::new();
+        assert!(matches!(
+            build_methods_multi_k(
+                vec![correct::MethodConfig::GapSize { confirm: 2 }],
+                &empty,
+                &[]
+            )
+            .err()
+            .and_then(|e| e.downcast::<error::Error>().ok()),
+            Some(error::Error::NoSolidSet)
+        ));
+    }
+
+    #[test]
+    fn record_edits() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACTGACGACCC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        let pipeline = CorrectionPipeline::builder(&set)
+            .method(correct::MethodConfig::One { confirm: 2 })
+            .forward_only(true)
+            .build();
+
+        let record = noodles::fasta::Record::new(
+            noodles::fasta::record::Definition::new("read", None),
+            noodles::fasta::record::Sequence::from(b"ACTGAGACCC".to_vec()),
+        );
+
+        let corrected = pipeline.correct_record(&record);
+
+        assert_eq!(SeqRecord::sequence(&corrected.record), b"ACTGACGACCC");
+        assert_eq!(corrected.length, 10);
+        assert!(corrected.events[0].outcome.is_corrected());
+        assert_eq!(
+            corrected.edits,
+            vec![correct::Edit {
+                pos: 5,
+                original: b"".to_vec(),
+                replacement: b"C".to_vec(),
+                method: "One",
+                scenario: Some("ScenarioOne::D".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn first_kmer() {
+        let refe = b"ACGTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTG";
+
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(refe, 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        let pipeline = CorrectionPipeline::builder(&set)
+            .method(correct::MethodConfig::One { confirm: 2 })
+            .build();
+
+        for (read, pos, original, replacement) in [
+            (
+                &b"ACTTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTG"[..],
+                2,
+                b'T',
+                b'G',
+            ),
+            (
+                &b"ACGAGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTG"[..],
+                3,
+                b'A',
+                b'T',
+            ),
+        ] {
+            let record = noodles::fasta::Record::new(
+                noodles::fasta::record::Definition::new("read", None),
+                noodles::fasta::record::Sequence::from(read.to_vec()),
+            );
+
+            let both = pipeline.correct_record(&record);
+            assert_eq!(SeqRecord::sequence(&both.record), refe);
+            assert_eq!(
+                both.edits,
+                vec![correct::Edit {
+                    pos,
+                    original: vec![original],
+                    replacement: vec![replacement],
+                    method: "One",
+                    scenario: Some("ScenarioOne::S".to_string()),
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn pipeline() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACTGACGACCC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        let pipeline = CorrectionPipeline::builder(&set)
+            .method(correct::MethodConfig::One { confirm: 2 })
+            .forward_only(true)
+            .build();
+
+        let record = noodles::fasta::Record::new(
+            noodles::fasta::record::Definition::new("read", None),
+            noodles::fasta::record::Sequence::from(b"ACTGAGACCC".to_vec()),
+        );
+
+        let corrected = pipeline.correct_record(&record);
+        assert_eq!(SeqRecord::sequence(&corrected.record), b"ACTGACGACCC");
+
+        let corrected: Vec<Corrected<noodles::fasta::Record>> = pipeline
+            .correct_iter(vec![Ok(record.clone()), Ok(record)].into_iter())
+            .collect::<std::io::Result<_>>()
+            .unwrap();
+        assert_eq!(corrected.len(), 2);
+        assert_eq!(corrected[1].edits.len(), 1);
+
+        let mut inputs = vec![&b">read\nACTGAGACCC\n"[..]];
+        let mut outputs = vec![Vec::new()];
+
+        let stats = pipeline
+            .correct_files(&[], &mut inputs, &mut outputs, None)
+            .unwrap();
+        assert_eq!(stats.corrected_reads, 1);
+        assert_eq!(outputs[0], b">read\nACTGACGACCC\n".to_vec());
+    }
+
+    #[test]
+    fn trim_base_set() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACTGACGACCC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+        let other: set::BoxKmerSet = Box::new(set::Pcon::new(pcon::solid::Solid::new(7)));
+
+        // first method use an empty set, trimming must still use base set
+        let pipeline = CorrectionPipeline::builder(&set)
+            .method_with_set(correct::MethodConfig::One { confirm: 2 }, &other)
+            .method(correct::MethodConfig::One { confirm: 2 })
+            .forward_only(true)
+            .trim(Some(trim::Trim::new(None)))
+            .build();
+
+        let mut inputs = vec![&b">1\nACTGACGACCC\n>2\nTTTTTTTTTT\n"[..]];
+        let mut outputs = vec![Vec::new()];
+
+        let stats = pipeline
+            .correct_files(&[], &mut inputs, &mut outputs, None)
+            .unwrap();
+        assert_eq!(stats.reads, 2);
+        assert_eq!(stats.trimmed_reads, 1);
+        assert_eq!(outputs[0], b">1\nACTGACGACCC\n".to_vec());
+    }
+
+    #[test]
+    fn record_order() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACTGACGACCC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        let pipeline = CorrectionPipeline::builder(&set)
+            .method(correct::MethodConfig::One { confirm: 2 })
+            .forward_only(true)
+            .record_buffer(4)
+            .build();
+
+        let mut input = Vec::new();
+        let mut expected = Vec::new();
+        for i in 0..100 {
+            input.extend_from_slice(format!(">{}\nACTGAGACCC\n", i).as_bytes());
+            expected.extend_from_slice(format!(">{}\nACTGACGACCC\n", i).as_bytes());
+        }
+
+        let mut inputs: Vec<Box<dyn std::io::BufRead>> =
+            vec![Box::new(std::io::Cursor::new(input))];
+        let mut output = Vec::new();
+        {
+            let mut outputs: Vec<Box<dyn std::io::Write + std::marker::Send + '_>> =
+                vec![Box::new(&mut output)];
+
+            let stats = pipeline
+                .correct_files(&[], &mut inputs, &mut outputs, None)
+                .unwrap();
+            assert_eq!(stats.corrected_reads, 100);
+        }
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn concurrent_files() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACTGACGACCC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        let pipeline = CorrectionPipeline::builder(&set)
+            .method(correct::MethodConfig::One { confirm: 2 })
+            .forward_only(true)
+            .record_buffer(8)
+            .concurrent_files(3)
+            .build();
+
+        let mut inputs: Vec<Box<dyn std::io::BufRead>> = Vec::new();
+        let mut expected = Vec::new();
+        for file in 0..5 {
+            let mut input = Vec::new();
+            let mut output = Vec::new();
+            for i in 0..20 {
+                input.extend_from_slice(format!(">{}_{}\nACTGAGACCC\n", file, i).as_bytes());
+                output.extend_from_slice(format!(">{}_{}\nACTGACGACCC\n", file, i).as_bytes());
+            }
+            inputs.push(Box::new(std::io::Cursor::new(input)));
+            expected.push(output);
+        }
+
+        let mut buffers = vec![Vec::new(); 5];
+        {
+            let mut outputs: Vec<Box<dyn std::io::Write + std::marker::Send + '_>> = buffers
+                .iter_mut()
+                .map(|buffer| Box::new(buffer) as Box<dyn std::io::Write + std::marker::Send + '_>)
+                .collect();
+
+            let stats = pipeline
+                .correct_files(&[], &mut inputs, &mut outputs, None)
+                .unwrap();
+            assert_eq!(stats.corrected_reads, 100);
+        }
+        assert_eq!(buffers, expected);
+
+        let mut outputs: Vec<Box<dyn std::io::Write + std::marker::Send>> =
+            vec![Box::new(Vec::new())];
+        assert!(pipeline
+            .correct_files(&[], &mut inputs, &mut outputs, None)
+            .is_err());
+    }
+
+    #[test]
+    fn output_error() {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACTGACGACCC", 5) {
+            data.set(kmer, true);
+        }
+
+        let set: set::BoxKmerSet = Box::new(set::Pcon::new(data));
+
+        let pipeline = CorrectionPipeline::builder(&set)
+            .method(correct::MethodConfig::One { confirm: 2 })
+            .record_buffer(4)
+            .build();
+
+        let mut input = Vec::new();
+        for i in 0..100 {
+            input.extend_from_slice(format!(">{}\nACTGAGACCC\n", i).as_bytes());
+        }
+
+        let mut inputs: Vec<Box<dyn std::io::BufRead>> =
+            vec![Box::new(std::io::Cursor::new(input))];
+        // output is full after first record
+        let mut buffer = [0u8; 20];
+        let mut outputs: Vec<Box<dyn std::io::Write + std::marker::Send + '_>> =
+            vec![Box::new(std::io::Cursor::new(&mut buffer[..]))];
+
+        assert!(pipeline
+            .correct_files(&[], &mut inputs, &mut outputs, None)
+            .is_err());
+    }
+
+    #[test]
+    fn fasta_conversion() {
+        let fasta = b">read\nACGT\n";
+
+        let mut output = Vec::new();
+        reads2fasta(&mut &fasta[..], &mut output).unwrap();
+        assert_eq!(output, fasta);
+
+        #[cfg(feature = "fastq")]
+        {
+            let mut output = Vec::new();
+            reads2fasta(&mut &b"@read\nACGT\n+\nIIII\n"[..], &mut output).unwrap();
+            assert_eq!(output, fasta);
+        }
+    }
 }
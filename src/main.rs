@@ -3,6 +3,7 @@
 #![warn(missing_docs)]
 
 /* std use */
+use std::io::Seek as _;
 
 /* crates use */
 
@@ -12,11 +13,12 @@ use clap::Parser as _;
 /* project use */
 use br::cli;
 use br::error;
+use br::kmer;
 use br::set;
 
 fn main() -> error::Result<()> {
     // parse cli
-    let params = cli::Command::parse();
+    let params = cli::Command::parse().load_config()?;
 
     // Setup logger
     stderrlog::new()
@@ -32,67 +34,263 @@ fn main() -> error::Result<()> {
         .num_threads(params.threads())
         .build_global()?;
 
-    let kmer_set = match params.subcommand {
-        cli::SubCommand::Count(ref subparams) => count(subparams),
-        cli::SubCommand::Fasta(ref subparams) => fasta(subparams),
-        cli::SubCommand::Solid(ref subparams) => solid(subparams),
-        cli::SubCommand::LargeKmer(ref subparams) => large_kmer(subparams),
-    }?;
+    params.dump_config()?;
+
+    match params.subcommand()? {
+        cli::SubCommand::Count(subparams) => {
+            correct(&params, vec![count(subparams)?], params.inputs()?)
+        }
+        cli::SubCommand::Fasta(subparams) if subparams.rounds() > 1 => rounds(&params, subparams),
+        cli::SubCommand::Fasta(subparams) => correct(&params, fasta(subparams)?, params.inputs()?),
+        cli::SubCommand::Solid(subparams) => correct(&params, solid(subparams)?, params.inputs()?),
+        cli::SubCommand::LargeKmer(subparams) => match subparams.max_kmer_size() {
+            0 => Err(error::Error::KmerSizeNull.into()),
+            1..=32 => correct(&params, large_kmer::<u64>(subparams)?, params.inputs()?),
+            33..=64 => correct(&params, large_kmer::<u128>(subparams)?, params.inputs()?),
+            k => Err(error::Error::KmerSizeTooLarge(k.into()).into()),
+        },
+    }
+}
 
-    let methods = br::build_methods(
-        params.corrections(),
-        &kmer_set,
-        params.confirm(),
-        params.max_search(),
-    );
-
-    br::run_correction(
-        &mut params.inputs()?,
-        &mut params.outputs()?,
-        methods,
-        params.two_side(),
-        params.record_buffer(),
+fn correct<K>(
+    params: &cli::Command,
+    kmer_sets: Vec<set::BoxKmerSet<K>>,
+    mut inputs: Vec<Box<dyn std::io::BufRead>>,
+) -> error::Result<()>
+where
+    K: kmer::Kmer,
+{
+    let mut report = params.report()?;
+    let mut outputs = params.outputs()?;
+    let stats = run(
+        params,
+        &kmer_sets,
+        &mut inputs,
+        &mut outputs,
+        report.as_mut(),
     )?;
+    for output in outputs {
+        output.finish()?;
+    }
+    if let Some(report) = report {
+        report.finish()?;
+    }
+
+    summary(params, &stats)?;
+    if let Some(writer) = params.stats()? {
+        stats.write_json(writer)?;
+    }
+
+    Ok(())
+}
+
+fn run<K, W>(
+    params: &cli::Command,
+    kmer_sets: &[set::BoxKmerSet<K>],
+    inputs: &mut [Box<dyn std::io::BufRead>],
+    outputs: &mut [W],
+    report: Option<&mut br::report::Report>,
+) -> error::Result<br::stats::Stats>
+where
+    K: kmer::Kmer,
+    W: std::io::Write + std::marker::Send,
+{
+    let builder = br::build_methods_multi_k(
+        params.method_configs(),
+        kmer_sets,
+        &params.method_kmer_sizes(),
+    )?
+    .into_iter()
+    .fold(
+        br::CorrectionPipeline::builder(&kmer_sets[0]),
+        |builder, corrector| builder.corrector(corrector),
+    )
+    .forward_only(params.two_side())
+    .record_buffer(params.record_buffer())
+    .insertion_quality(params.insertion_quality())
+    .ambiguous(params.ambiguous())
+    .trim(params.trim())
+    .solid_ratio(params.solid_ratio())
+    .skip_invalid(params.skip_invalid());
+
+    #[cfg(feature = "parallel")]
+    let builder = builder.concurrent_files(params.concurrent_files());
+
+    let pipeline = builder.build();
+
+    pipeline.correct_files(&params.input_names(), inputs, outputs, report)
+}
+
+/// Write a summary of correction statistics on stderr, quiet silence it
+fn summary(params: &cli::Command, stats: &br::stats::Stats) -> error::Result<()> {
+    if !params.quiet() {
+        stats.write_summary(std::io::stderr().lock())?;
+    }
 
     Ok(())
 }
 
+fn rounds(params: &cli::Command, subparams: &cli::Fasta) -> error::Result<()> {
+    let mut kmer_sets = fasta(subparams)?;
+    let mut inputs = params.inputs()?;
+
+    for round in 1..subparams.rounds() {
+        // corrected reads of round are store in anonymous temporary files, they are delete when last handle is drop
+        let files = inputs
+            .iter()
+            .map(|_| tempfile::tempfile())
+            .collect::<std::io::Result<Vec<std::fs::File>>>()?;
+        {
+            let mut outputs = files
+                .iter()
+                .map(|file| {
+                    Ok(Box::new(std::io::BufWriter::new(file.try_clone()?))
+                        as Box<dyn std::io::Write + std::marker::Send>)
+                })
+                .collect::<error::Result<Vec<Box<dyn std::io::Write + std::marker::Send>>>>()?;
+
+            let stats = run(params, &kmer_sets, &mut inputs, &mut outputs, None)?;
+            log::info!("round {} of {}", round, subparams.rounds());
+            summary(params, &stats)?;
+
+            for output in outputs.iter_mut() {
+                output.flush()?;
+            }
+        }
+
+        let mut reads = std::io::BufWriter::new(tempfile::tempfile()?);
+        for file in files.iter() {
+            br::reads2fasta(&mut rewind(file)?, &mut reads)?;
+        }
+        let reads = reads.into_inner().map_err(|e| e.into_error())?;
+
+        kmer_sets = vec![Box::new(count_reads(
+            Box::new(rewind(&reads)?),
+            subparams.round_kmer_size(round),
+            subparams,
+            None,
+        )?)];
+        for kmer_size in subparams.extra_kmer_sizes() {
+            kmer_sets.push(Box::new(count_reads(
+                Box::new(rewind(&reads)?),
+                kmer_size,
+                subparams,
+                None,
+            )?));
+        }
+
+        inputs = files
+            .iter()
+            .map(|file| Ok(Box::new(rewind(file)?) as Box<dyn std::io::BufRead>))
+            .collect::<error::Result<Vec<Box<dyn std::io::BufRead>>>>()?;
+    }
+
+    correct(params, kmer_sets, inputs)
+}
+
+/// Open a new reader at begin of a temporary file
+fn rewind(file: &std::fs::File) -> error::Result<std::io::BufReader<std::fs::File>> {
+    let mut file = file.try_clone()?;
+    file.seek(std::io::SeekFrom::Start(0))?;
+
+    Ok(std::io::BufReader::new(file))
+}
+
 fn count(subparams: &br::cli::Count) -> error::Result<Box<dyn br::set::KmerSet>> {
     let counter =
         pcon::counter::Counter::<pcon::CountTypeNoAtomic>::from_stream(subparams.inputs()?)?;
 
-    count2solid(
+    let set = count2solid(
         counter.raw(),
         subparams.abundance(),
         subparams.abundance_selection(),
         counter.k(),
-    )
+        subparams.spectrum()?,
+    )?;
+
+    save_solid(&set, subparams.save_solid()?, subparams.save_format())?;
+
+    Ok(Box::new(set))
+}
+
+fn fasta(subparams: &br::cli::Fasta) -> error::Result<Vec<Box<dyn br::set::KmerSet>>> {
+    let set = count_reads(
+        subparams.inputs()?,
+        subparams.round_kmer_size(0),
+        subparams,
+        subparams.spectrum()?,
+    )?;
+
+    save_solid(&set, subparams.save_solid()?, subparams.save_format())?;
+
+    let mut sets: Vec<Box<dyn br::set::KmerSet>> = vec![Box::new(set)];
+    for kmer_size in subparams.extra_kmer_sizes() {
+        sets.push(Box::new(count_reads(
+            subparams.inputs()?,
+            kmer_size,
+            subparams,
+            None,
+        )?));
+    }
+
+    Ok(sets)
 }
 
-fn fasta(subparams: &br::cli::Fasta) -> error::Result<Box<dyn br::set::KmerSet>> {
-    let mut counter = pcon::counter::Counter::<pcon::CountType>::new(subparams.kmer_size());
-    counter.count_fasta(subparams.inputs()?, 8192);
+fn count_reads(
+    input: Box<dyn std::io::BufRead>,
+    kmer_size: u8,
+    subparams: &br::cli::Fasta,
+    spectrum_output: Option<Box<dyn std::io::Write + std::marker::Send>>,
+) -> error::Result<set::Pcon> {
+    if kmer_size == 0 {
+        return Err(error::Error::KmerSizeNull.into());
+    } else if kmer_size > <u64 as kmer::Kmer>::MAX_K {
+        return Err(error::Error::KmerSizeTooLarge(kmer_size.into()).into());
+    }
+
+    let mut counter = pcon::counter::Counter::<pcon::CountType>::new(kmer_size);
+    counter.count_fasta(input, 8192);
 
     let raw = unsafe {
         std::mem::transmute::<&[pcon::CountType], &[pcon::CountTypeNoAtomic]>(counter.raw())
     };
+
     count2solid(
         raw,
         subparams.abundance(),
         subparams.abundance_selection(),
         counter.k(),
+        spectrum_output,
     )
 }
 
+fn save_solid(
+    set: &set::Pcon,
+    output: Option<Box<dyn std::io::Write + std::marker::Send>>,
+    format: cli::SolidOutput,
+) -> error::Result<()> {
+    if let Some(writer) = output {
+        match format {
+            cli::SolidOutput::Solid => set.write_solid(writer)?,
+            cli::SolidOutput::Fasta => set.write_fasta(writer)?,
+            cli::SolidOutput::Mmap => set.write_mmap(writer)?,
+            cli::SolidOutput::MmapBitfield => set.write_mmap_bitfield(writer)?,
+        }
+    }
+
+    Ok(())
+}
+
 fn count2solid(
     counter: &[pcon::CountTypeNoAtomic],
     abundance: Option<pcon::CountTypeNoAtomic>,
     abundance_selection: Option<cli::AbundanceSelection>,
     kmer_size: pcon::CountTypeNoAtomic,
-) -> error::Result<Box<dyn br::set::KmerSet>> {
+    spectrum_output: Option<Box<dyn std::io::Write + std::marker::Send>>,
+) -> error::Result<set::Pcon> {
     let spectrum = pcon::spectrum::Spectrum::from_count(counter);
 
-    let abundance = match (abundance, abundance_selection) {
+    let threshold = match (abundance, abundance_selection) {
         (Some(x), _) => Ok(x),
         (_, Some(cli::AbundanceSelection::FirstMinimum)) => spectrum
             .get_threshold(pcon::spectrum::ThresholdMethod::FirstMinimum, 0.0)
@@ -109,55 +307,122 @@ fn count2solid(
         (None, None) => Err(error::Error::AbundanceThresholdOrAbundanceMethod),
     }?;
 
-    Ok(Box::new(set::Pcon::new(pcon::solid::Solid::from_count(
-        kmer_size, counter, abundance,
-    ))))
+    let method = match (abundance, abundance_selection) {
+        (Some(_), _) => "abundance".to_string(),
+        (_, Some(selection)) => selection.name(),
+        (None, None) => unreachable!(),
+    };
+    log::info!("solid threshold {} select by {}", threshold, method);
+
+    if let Some(writer) = spectrum_output {
+        br::spectrum::write_csv(
+            writer,
+            &br::spectrum::histogram(counter),
+            threshold,
+            &method,
+        )?;
+    }
+
+    Ok(set::Pcon::new(pcon::solid::Solid::from_count(
+        kmer_size, counter, threshold,
+    )))
 }
 
-fn solid(subparams: &br::cli::Solid) -> error::Result<Box<dyn br::set::KmerSet>> {
-    let set = match subparams.format() {
-        cli::SolidInput::Solid => set::Pcon::from_pcon_solid(subparams.input()?)?,
-        #[cfg(feature = "csv")]
-        cli::SolidInput::Csv => set::Pcon::from_csv(
-            subparams.input()?,
-            subparams
-                .kmer_size()
-                .ok_or(error::Error::SolidRequireKmerSize)?,
-        )?,
-        cli::SolidInput::Fasta => set::Pcon::from_fasta(
-            subparams.input()?,
-            subparams
-                .kmer_size()
-                .ok_or(error::Error::SolidRequireKmerSize)?,
-        ),
-        #[cfg(feature = "fastq")]
-        cli::SolidInput::Fastq => set::Pcon::from_fastq(
-            subparams.input()?,
-            subparams
-                .kmer_size()
-                .ok_or(error::Error::SolidRequireKmerSize)?,
-        ),
-        #[cfg(feature = "kff")]
-        cli::SolidInput::Kff => todo!(),
-    };
+fn solid(subparams: &br::cli::Solid) -> error::Result<Vec<Box<dyn br::set::KmerSet>>> {
+    let mut sets: Vec<Box<dyn br::set::KmerSet>> = Vec::new();
 
-    Ok(Box::new(set))
+    for index in 0..subparams.inputs().len() {
+        let set = match subparams.format() {
+            cli::SolidInput::Solid => set::Pcon::from_pcon_solid(subparams.input(index)?)?,
+            cli::SolidInput::Mmap => {
+                sets.push(Box::new(set::Mmap::<u64>::open(
+                    &subparams.inputs()[index],
+                    subparams.kmer_size(index),
+                )?));
+                continue;
+            }
+            cli::SolidInput::MmapBitfield => {
+                sets.push(Box::new(set::Bitfield::open(&subparams.inputs()[index])?));
+                continue;
+            }
+            #[cfg(feature = "csv")]
+            cli::SolidInput::Csv => set::Pcon::from_csv(
+                subparams.input(index)?,
+                subparams
+                    .kmer_size(index)
+                    .ok_or(error::Error::SolidRequireKmerSize)?,
+            )?,
+            cli::SolidInput::Fasta => set::Pcon::from_fasta(
+                subparams.input(index)?,
+                subparams.inputs()[index].to_string_lossy().to_string(),
+                subparams
+                    .kmer_size(index)
+                    .ok_or(error::Error::SolidRequireKmerSize)?,
+            )?,
+            #[cfg(feature = "fastq")]
+            cli::SolidInput::Fastq => set::Pcon::from_fastq(
+                subparams.input(index)?,
+                subparams.inputs()[index].to_string_lossy().to_string(),
+                subparams
+                    .kmer_size(index)
+                    .ok_or(error::Error::SolidRequireKmerSize)?,
+            )?,
+            #[cfg(feature = "kff")]
+            cli::SolidInput::Kff => {
+                set::Pcon::from_kff(subparams.input(index)?, subparams.kmer_size(index))?
+            }
+        };
+
+        sets.push(Box::new(set));
+    }
+
+    Ok(sets)
 }
 
-fn large_kmer(subparams: &br::cli::LargeKmer) -> error::Result<Box<dyn br::set::KmerSet>> {
-    let set = match subparams.format() {
-        #[cfg(feature = "csv")]
-        cli::LargeKmerInput::Csv => set::Hash::from_csv(subparams.input()?, subparams.kmer_size())?,
-        cli::LargeKmerInput::Fasta => {
-            set::Hash::from_fasta(subparams.input()?, subparams.kmer_size())
-        }
-        #[cfg(feature = "fastq")]
-        cli::LargeKmerInput::Fastq => {
-            set::Hash::from_fastq(subparams.input()?, subparams.kmer_size())
+fn large_kmer<K>(subparams: &br::cli::LargeKmer) -> error::Result<Vec<set::BoxKmerSet<'static, K>>>
+where
+    K: kmer::Kmer,
+{
+    let mut sets: Vec<set::BoxKmerSet<'static, K>> = Vec::new();
+
+    for index in 0..subparams.inputs().len() {
+        let kmer_size = subparams.kmer_size(index);
+
+        let set = match subparams.format() {
+            cli::LargeKmerInput::Mmap => {
+                sets.push(Box::new(set::Mmap::<K>::open(
+                    &subparams.inputs()[index],
+                    Some(kmer_size),
+                )?));
+                continue;
+            }
+            #[cfg(feature = "csv")]
+            cli::LargeKmerInput::Csv => {
+                set::Hash::<K>::from_csv(subparams.input(index)?, kmer_size)?
+            }
+            cli::LargeKmerInput::Fasta => set::Hash::<K>::from_fasta(
+                subparams.input(index)?,
+                subparams.inputs()[index].to_string_lossy().to_string(),
+                kmer_size,
+            )?,
+            #[cfg(feature = "fastq")]
+            cli::LargeKmerInput::Fastq => set::Hash::<K>::from_fastq(
+                subparams.input(index)?,
+                subparams.inputs()[index].to_string_lossy().to_string(),
+                kmer_size,
+            )?,
+            #[cfg(feature = "kff")]
+            cli::LargeKmerInput::Kff => {
+                set::Hash::<K>::from_kff(subparams.input(index)?, Some(kmer_size))?
+            }
+        };
+
+        if let Some(writer) = subparams.save_solid(index)? {
+            set.write_mmap(writer)?;
         }
-        #[cfg(feature = "kff")]
-        cli::LargeKmerInput::Kff => todo!(),
-    };
 
-    Ok(Box::new(set))
+        sets.push(Box::new(set));
+    }
+
+    Ok(sets)
 }
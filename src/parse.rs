@@ -0,0 +1,244 @@
+//! Parsing of input records with localisation of invalid records
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::error;
+
+/// Reader wrapper that count number of bytes consumed, offset is share with record iterator
+pub struct CountReader<R> {
+    inner: R,
+    offset: std::rc::Rc<std::cell::Cell<u64>>,
+}
+
+impl<R> CountReader<R> {
+    /// Create a new counting reader, and return shared offset
+    pub fn new(inner: R) -> (Self, std::rc::Rc<std::cell::Cell<u64>>) {
+        let offset = std::rc::Rc::new(std::cell::Cell::new(0));
+
+        (
+            Self {
+                inner,
+                offset: offset.clone(),
+            },
+            offset,
+        )
+    }
+}
+
+impl<R> std::io::Read for CountReader<R>
+where
+    R: std::io::Read,
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.offset.set(self.offset.get() + len as u64);
+
+        Ok(len)
+    }
+}
+
+impl<R> std::io::BufRead for CountReader<R>
+where
+    R: std::io::BufRead,
+{
+    fn fill_buf(&mut self) -> std::io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.offset.set(self.offset.get() + amt as u64);
+        self.inner.consume(amt)
+    }
+}
+
+/// Iterator on records of an input, parse error are report with input name, record index and byte offset
+pub struct Records<I> {
+    records: I,
+    name: String,
+    offset: std::rc::Rc<std::cell::Cell<u64>>,
+    index: u64,
+    skip_invalid: bool,
+}
+
+impl<I> Records<I> {
+    /// Create a new record iterator, if skip_invalid is true invalid records are log and skip
+    pub fn new(
+        records: I,
+        name: String,
+        offset: std::rc::Rc<std::cell::Cell<u64>>,
+        skip_invalid: bool,
+    ) -> Self {
+        Self {
+            records,
+            name,
+            offset,
+            index: 0,
+            skip_invalid,
+        }
+    }
+}
+
+impl<I, R> Iterator for Records<I>
+where
+    I: Iterator<Item = std::io::Result<R>>,
+{
+    type Item = error::Result<R>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let offset = self.offset.get();
+            let result = self.records.next()?;
+            let index = self.index;
+            self.index += 1;
+
+            match result {
+                Ok(record) => return Some(Ok(record)),
+                Err(error) => {
+                    // skip only parse error that consume input, other error can't be recover
+                    let recoverable = error.kind() == std::io::ErrorKind::InvalidData
+                        && self.offset.get() > offset;
+
+                    let error = error::Error::InvalidRecord {
+                        name: self.name.clone(),
+                        index,
+                        offset,
+                        error,
+                    };
+
+                    if self.skip_invalid && recoverable {
+                        log::warn!("{}, record skipped", error);
+                    } else {
+                        return Some(Err(error.into()));
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Check quality length of a fastq record match its sequence length
+#[cfg(feature = "fastq")]
+pub fn check_quality(
+    record: std::io::Result<noodles::fastq::Record>,
+) -> std::io::Result<noodles::fastq::Record> {
+    let record = record?;
+
+    if record.quality_scores().len() != record.sequence().len() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "quality length {} differ from sequence length {}",
+                record.quality_scores().len(),
+                record.sequence().len()
+            ),
+        ));
+    }
+
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count() {
+        let data: &[u8] = b">1\nACGT\n>2\nGGGG\n";
+        let (input, offset) = CountReader::new(data);
+        let mut reader = noodles::fasta::Reader::new(input);
+
+        let mut records = reader.records();
+        records.next().unwrap().unwrap();
+        assert_eq!(offset.get(), 8);
+        records.next().unwrap().unwrap();
+        assert_eq!(offset.get(), 16);
+    }
+
+    #[test]
+    fn invalid() {
+        let offset = std::rc::Rc::new(std::cell::Cell::new(42));
+        let records = || {
+            vec![
+                Ok(b"ACGT".to_vec()),
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    "invalid definition",
+                )),
+            ]
+            .into_iter()
+        };
+
+        let result = Records::new(records(), "reads.fasta".to_string(), offset.clone(), false)
+            .collect::<Vec<_>>();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0].as_ref().unwrap(), b"ACGT");
+        assert_eq!(
+            result[1].as_ref().unwrap_err().to_string(),
+            "Invalid record 1 of input reads.fasta at byte 42: invalid definition"
+        );
+
+        // error didn't consume input, it can't be skip
+        let result =
+            Records::new(records(), "reads.fasta".to_string(), offset, true).collect::<Vec<_>>();
+        assert_eq!(result.len(), 2);
+        assert!(result[1].is_err());
+    }
+
+    #[test]
+    fn skip() {
+        let data: &[u8] = b">1\nACGT\n>2\nGGGG\n>3\nTTTT\n";
+        let (input, offset) = CountReader::new(data);
+        let mut reader = noodles::fasta::Reader::new(input);
+
+        let records = reader.records().map(|record| match record {
+            Ok(record) if record.name() == b"2" => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "invalid record",
+            )),
+            other => other,
+        });
+
+        let names = Records::new(records, "reads.fasta".to_string(), offset, true)
+            .map(|record| record.unwrap().name().to_vec())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names, vec![b"1".to_vec(), b"3".to_vec()]);
+    }
+
+    #[cfg(feature = "fastq")]
+    #[test]
+    fn quality() {
+        let data: &[u8] = b"@1\nACGT\n+\nIIII\n@2\nACGT\n+\nII\n@3\nTTTT\n+\nIIII\n";
+        let (input, offset) = CountReader::new(data);
+        let mut reader = noodles::fastq::Reader::new(input);
+
+        let result = Records::new(
+            reader.records().map(check_quality),
+            "reads.fastq".to_string(),
+            offset.clone(),
+            false,
+        )
+        .collect::<Vec<_>>();
+        assert_eq!(result.len(), 3);
+        assert!(result[0].is_ok());
+        assert_eq!(
+            result[1].as_ref().unwrap_err().to_string(),
+            "Invalid record 1 of input reads.fastq at byte 15: quality length 2 differ from sequence length 4"
+        );
+
+        let (input, offset) = CountReader::new(data);
+        let mut reader = noodles::fastq::Reader::new(input);
+        let names = Records::new(
+            reader.records().map(check_quality),
+            "reads.fastq".to_string(),
+            offset,
+            true,
+        )
+        .map(|record| record.unwrap().name().to_vec())
+        .collect::<Vec<_>>();
+        assert_eq!(names, vec![b"1".to_vec(), b"3".to_vec()]);
+    }
+}
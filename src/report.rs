@@ -0,0 +1,177 @@
+//! Correction report
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::correct;
+
+/// Format of correction report
+#[derive(std::fmt::Debug, std::clone::Clone, std::marker::Copy, std::cmp::PartialEq)]
+pub enum Format {
+    /// Tabulation separated values with a header
+    Tsv,
+
+    /// One json object per line
+    Jsonl,
+}
+
+/// Write one row per correction apply on a read
+pub struct Report {
+    writer: Box<dyn std::io::Write + std::marker::Send>,
+    format: Format,
+}
+
+impl Report {
+    /// Create a new report, tsv header is write immediately
+    pub fn new(
+        mut writer: Box<dyn std::io::Write + std::marker::Send>,
+        format: Format,
+    ) -> std::io::Result<Self> {
+        if let Format::Tsv = format {
+            writeln!(
+                writer,
+                "read\tposition\toriginal\treplacement\tmethod\tscenario"
+            )?;
+        }
+
+        Ok(Self { writer, format })
+    }
+
+    /// Write all edits of a read
+    pub fn write(&mut self, name: &[u8], edits: &[correct::Edit]) -> std::io::Result<()> {
+        let name = String::from_utf8_lossy(name);
+
+        for edit in edits {
+            match self.format {
+                Format::Tsv => writeln!(
+                    self.writer,
+                    "{}\t{}\t{}\t{}\t{}\t{}",
+                    name,
+                    edit.pos,
+                    bases(&edit.original),
+                    bases(&edit.replacement),
+                    edit.method,
+                    edit.scenario.as_deref().unwrap_or("-"),
+                )?,
+                Format::Jsonl => writeln!(
+                    self.writer,
+                    "{{\"read\":\"{}\",\"position\":{},\"original\":\"{}\",\"replacement\":\"{}\",\"method\":\"{}\",\"scenario\":{}}}",
+                    json_escape(&name),
+                    edit.pos,
+                    String::from_utf8_lossy(&edit.original),
+                    String::from_utf8_lossy(&edit.replacement),
+                    edit.method,
+                    edit.scenario
+                        .as_ref()
+                        .map_or("null".to_string(), |s| format!("\"{}\"", json_escape(s))),
+                )?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Flush report, error of the last writes are only report here
+    pub fn finish(mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+fn bases(seq: &[u8]) -> std::borrow::Cow<str> {
+    if seq.is_empty() {
+        std::borrow::Cow::Borrowed("-")
+    } else {
+        String::from_utf8_lossy(seq)
+    }
+}
+
+pub(crate) fn json_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read as _;
+
+    fn edits() -> Vec<correct::Edit> {
+        vec![
+            correct::Edit {
+                pos: 5,
+                original: b"T".to_vec(),
+                replacement: b"C".to_vec(),
+                method: "One",
+                scenario: Some("ScenarioOne::S".to_string()),
+            },
+            correct::Edit {
+                pos: 42,
+                original: b"".to_vec(),
+                replacement: b"GA".to_vec(),
+                method: "Graph",
+                scenario: None,
+            },
+        ]
+    }
+
+    fn write(format: Format) -> String {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+
+        let mut report = Report::new(
+            Box::new(std::io::BufWriter::new(file.reopen().unwrap())),
+            format,
+        )
+        .unwrap();
+        report.write(b"read_1", &edits()).unwrap();
+        report.write(b"read\"2", &[]).unwrap();
+        report.finish().unwrap();
+
+        let mut content = String::new();
+        file.read_to_string(&mut content).unwrap();
+
+        content
+    }
+
+    #[test]
+    fn tsv() {
+        assert_eq!(
+            write(Format::Tsv),
+            "read\tposition\toriginal\treplacement\tmethod\tscenario
+read_1\t5\tT\tC\tOne\tScenarioOne::S
+read_1\t42\t-\tGA\tGraph\t-
+"
+        );
+    }
+
+    #[test]
+    fn jsonl() {
+        assert_eq!(
+            write(Format::Jsonl),
+            "{\"read\":\"read_1\",\"position\":5,\"original\":\"T\",\"replacement\":\"C\",\"method\":\"One\",\"scenario\":\"ScenarioOne::S\"}
+{\"read\":\"read_1\",\"position\":42,\"original\":\"\",\"replacement\":\"GA\",\"method\":\"Graph\",\"scenario\":null}
+"
+        );
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(json_escape("read\"1\\"), "read\\\"1\\\\");
+        assert_eq!(json_escape("a\tb"), "a\\tb");
+    }
+}
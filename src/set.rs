@@ -5,19 +5,145 @@
 /* crate use */
 
 /* project use */
+#[cfg(feature = "kff")]
+use crate::error;
+use crate::kmer;
 
 /* mod declaration */
 pub mod hash;
+pub mod mmap;
 pub mod pcon;
 
 /* reexport */
 pub use self::hash::Hash;
+pub use self::mmap::Bitfield;
+pub use self::mmap::Mmap;
 pub use self::pcon::Pcon;
 
-pub trait KmerSet: Sync {
-    fn get(&self, kmer: u64) -> bool;
+pub trait KmerSet<K = u64>: Sync
+where
+    K: kmer::Kmer,
+{
+    fn get(&self, kmer: K) -> bool;
 
     fn k(&self) -> u8;
 }
 
-pub type BoxKmerSet<'a> = Box<dyn KmerSet + 'a>;
+pub type BoxKmerSet<'a, K = u64> = Box<dyn KmerSet<K> + 'a>;
+
+#[cfg(feature = "kff")]
+/// Open a kff file, kmer size is read in file and must match k if it's set
+pub(crate) fn open_kff<R>(input: R, k: Option<u8>) -> error::Result<(kff::Kff<R>, u8)>
+where
+    R: std::io::Read,
+{
+    let reader = kff::Kff::read(input)?;
+
+    let file_k = *reader
+        .values()
+        .get("k")
+        .ok_or(error::Error::KffMissingKmerSize)?;
+    let file_k = u8::try_from(file_k).map_err(|_| error::Error::KmerSizeTooLarge(file_k))?;
+
+    match k {
+        Some(k) if k != file_k => Err(error::Error::KffKmerSizeMismatch {
+            expected: k,
+            found: file_k,
+        }
+        .into()),
+        _ => Ok((reader, file_k)),
+    }
+}
+
+#[cfg(feature = "kff")]
+/// Call f on sequence of each kmer store in raw and minimizer section of kff file
+pub(crate) fn kff_kmers<R, F>(mut reader: kff::Kff<R>, k: u8, mut f: F) -> error::Result<()>
+where
+    R: std::io::Read,
+    F: FnMut(&[u8]),
+{
+    let nucs = kff_decoding(reader.header().encoding());
+    let mut seq = Vec::with_capacity(k as usize);
+
+    let mut kmers = reader.kmers();
+    while let Some(kmer) = kmers.next() {
+        let kmer = kmer?;
+        let bits = kmer.seq();
+
+        if bits.len() < 2 * k as usize {
+            return Err(error::Error::KffKmerTooShort.into());
+        }
+
+        seq.clear();
+        for pair in bits[bits.len() - 2 * k as usize..].chunks(2) {
+            seq.push(nucs[((pair[0] as usize) << 1) | pair[1] as usize]);
+        }
+
+        f(&seq);
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "kff")]
+/// Build table to convert a kff 2 bits value in nucleotide, encoding store A, C, G, T value from higher to lower bits
+fn kff_decoding(encoding: u8) -> [u8; 4] {
+    let mut nucs = [b'A'; 4];
+
+    for (shift, nuc) in [(6, b'A'), (4, b'C'), (2, b'G'), (0, b'T')] {
+        nucs[((encoding >> shift) & 0b11) as usize] = nuc;
+    }
+
+    nucs
+}
+
+#[cfg(feature = "kff")]
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decoding() {
+        // A = 0, C = 1, T = 2, G = 3
+        assert_eq!(kff_decoding(0b00011110), [b'A', b'C', b'T', b'G']);
+        // A = 0, C = 1, G = 2, T = 3
+        assert_eq!(kff_decoding(0b00011011), [b'A', b'C', b'G', b'T']);
+    }
+
+    #[test]
+    fn from_kff() -> error::Result<()> {
+        use crate::kmer::Kmer as _;
+
+        // raw section store ACGTGGA, minimizer section store TTGACCA with minimizer GAC
+        let open =
+            || std::io::BufReader::new(std::fs::File::open("tests/data/raw.k5.kff").unwrap());
+
+        let pcon = Pcon::from_kff(open(), None)?;
+        let hash = Hash::<u64>::from_kff(open(), Some(5))?;
+        assert_eq!(KmerSet::k(&pcon), 5);
+        assert_eq!(KmerSet::k(&hash), 5);
+
+        for kmer in [b"ACGTG", b"CGTGG", b"GTGGA", b"TTGAC", b"TGACC", b"GACCA"] {
+            let kmer = u64::seq2bit(kmer);
+
+            assert!(pcon.get(kmer));
+            assert!(pcon.get(kmer.revcomp(5)));
+            assert!(hash.get(kmer));
+            assert!(hash.get(kmer.revcomp(5)));
+        }
+        assert!(!pcon.get(u64::seq2bit(b"AAAAA")));
+        assert!(!hash.get(u64::seq2bit(b"AAAAA")));
+
+        let error = Pcon::from_kff(open(), Some(7)).unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<error::Error>(),
+            Some(error::Error::KffKmerSizeMismatch {
+                expected: 7,
+                found: 5
+            })
+        ));
+        assert!(Hash::<u128>::from_kff(open(), Some(7)).is_err());
+
+        Ok(())
+    }
+}
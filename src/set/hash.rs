@@ -7,16 +7,20 @@
 use rayon::prelude::*;
 
 /* project use */
-#[cfg(feature = "csv")]
 use crate::error;
+use crate::kmer;
+use crate::parse;
 use crate::set;
 
-pub struct Hash {
-    set: rustc_hash::FxHashSet<u64>,
+pub struct Hash<K = u64> {
+    set: rustc_hash::FxHashSet<K>,
     k: u8,
 }
 
-impl Hash {
+impl<K> Hash<K>
+where
+    K: kmer::Kmer,
+{
     #[cfg(feature = "csv")]
     pub fn from_csv<R>(input: R, k: u8) -> error::Result<Self>
     where
@@ -28,28 +32,46 @@ impl Hash {
         for result in reader.byte_records() {
             let record = result?;
 
-            set.insert(cocktail::kmer::canonical(
-                cocktail::kmer::seq2bit(record.get(0).ok_or(error::Error::CsvMissingFirstColumn)?),
-                k,
-            ));
+            set.insert(
+                K::seq2bit(record.get(0).ok_or(error::Error::CsvMissingFirstColumn)?).canonical(k),
+            );
+        }
+
+        Ok(Self { set, k })
+    }
+
+    #[cfg(feature = "kff")]
+    pub fn from_kff<R>(input: R, k: Option<u8>) -> error::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        let (reader, k) = set::open_kff(input, k)?;
+        if k > K::MAX_K {
+            return Err(error::Error::KmerSizeTooLarge(k.into()).into());
         }
 
+        let mut set = rustc_hash::FxHashSet::default();
+        set::kff_kmers(reader, k, |seq| {
+            set.insert(K::seq2bit(seq).canonical(k));
+        })?;
+
         Ok(Self { set, k })
     }
 
     #[cfg(not(feature = "parallel"))]
-    pub fn from_fasta<R>(input: R, k: u8) -> Self
+    pub fn from_fasta<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = rustc_hash::FxHashSet::default();
 
+        let (input, offset) = parse::CountReader::new(input);
         let mut reader = noodles::fasta::Reader::new(input);
-        let mut records = reader.records();
 
-        while let Some(Ok(record)) = records.next() {
+        for record in parse::Records::new(reader.records(), name, offset, false) {
+            let record = record?;
             if record.sequence().len() >= k as usize {
-                let kmerizer = cocktail::tokenizer::Canonical::new(record.sequence().as_ref(), k);
+                let kmerizer = kmer::Canonical::<K>::new(record.sequence().as_ref(), k)?;
 
                 for canonical in kmerizer {
                     set.insert(canonical);
@@ -57,66 +79,67 @@ impl Hash {
             }
         }
 
-        Self { set, k }
+        Ok(Self { set, k })
     }
 
     #[cfg(feature = "parallel")]
-    pub fn from_fasta<R>(input: R, k: u8) -> Self
+    pub fn from_fasta<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = rustc_hash::FxHashSet::default();
 
+        let (input, offset) = parse::CountReader::new(input);
         let mut reader = noodles::fasta::Reader::new(input);
-        let mut iter = reader.records();
+        let mut iter = parse::Records::new(reader.records(), name, offset, false);
         let mut records = Vec::with_capacity(8192);
 
         let mut end = true;
         while end {
             log::info!("Start populate buffer");
-            end = crate::populate_buffer(&mut iter, &mut records, 8192);
+            end = crate::populate_buffer(&mut iter, &mut records, 8192)?;
             log::info!("End populate buffer {}", records.len());
 
             set.extend(
                 records
                     .par_iter()
                     .filter(|record| record.sequence().len() >= k as usize)
-                    .map(|record| {
+                    .map(|record| -> error::Result<rustc_hash::FxHashSet<K>> {
                         let mut set = rustc_hash::FxHashSet::default();
-                        let kmerizer =
-                            cocktail::tokenizer::Canonical::new(record.sequence().as_ref(), k);
+                        let kmerizer = kmer::Canonical::<K>::new(record.sequence().as_ref(), k)?;
                         for canonical in kmerizer {
                             set.insert(canonical);
                         }
-                        set
+                        Ok(set)
                     })
-                    .reduce(
+                    .try_reduce(
                         || rustc_hash::FxHashSet::default(),
                         |mut x, y| {
                             x.extend(y.iter());
-                            x
+                            Ok(x)
                         },
-                    ),
+                    )?,
             );
         }
 
-        Self { set, k }
+        Ok(Self { set, k })
     }
 
     #[cfg(feature = "fastq")]
     #[cfg(not(feature = "parallel"))]
-    pub fn from_fastq<R>(input: R, k: u8) -> Self
+    pub fn from_fastq<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = rustc_hash::FxHashSet::default();
 
-        let mut reader = noodles::fasta::Reader::new(input);
-        let mut records = reader.records();
+        let (input, offset) = parse::CountReader::new(input);
+        let mut reader = noodles::fastq::Reader::new(input);
 
-        while let Some(Ok(record)) = records.next() {
+        for record in parse::Records::new(reader.records(), name, offset, false) {
+            let record = record?;
             if record.sequence().len() >= k as usize {
-                let kmerizer = cocktail::tokenizer::Canonical::new(record.sequence().as_ref(), k);
+                let kmerizer = kmer::Canonical::<K>::new(record.sequence().as_ref(), k)?;
 
                 for canonical in kmerizer {
                     set.insert(canonical);
@@ -124,57 +147,68 @@ impl Hash {
             }
         }
 
-        Self { set, k }
+        Ok(Self { set, k })
     }
 
     #[cfg(feature = "fastq")]
     #[cfg(feature = "parallel")]
-    pub fn from_fastq<R>(input: R, k: u8) -> Self
+    pub fn from_fastq<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = rustc_hash::FxHashSet::default();
 
+        let (input, offset) = parse::CountReader::new(input);
         let mut reader = noodles::fastq::Reader::new(input);
-        let mut iter = reader.records();
+        let mut iter = parse::Records::new(reader.records(), name, offset, false);
         let mut records = Vec::with_capacity(8192);
 
         let mut end = true;
         while end {
             log::info!("Start populate buffer");
-            end = crate::populate_bufferq(&mut iter, &mut records, 8192);
+            end = crate::populate_buffer(&mut iter, &mut records, 8192)?;
             log::info!("End populate buffer {}", records.len());
 
             set.extend(
                 records
                     .par_iter()
                     .filter(|record| record.sequence().len() >= k as usize)
-                    .map(|record| {
+                    .map(|record| -> error::Result<rustc_hash::FxHashSet<K>> {
                         let mut set = rustc_hash::FxHashSet::default();
-                        let kmerizer =
-                            cocktail::tokenizer::Canonical::new(record.sequence().as_ref(), k);
+                        let kmerizer = kmer::Canonical::<K>::new(record.sequence().as_ref(), k)?;
                         for canonical in kmerizer {
                             set.insert(canonical);
                         }
-                        set
+                        Ok(set)
                     })
-                    .reduce(
+                    .try_reduce(
                         || rustc_hash::FxHashSet::default(),
                         |mut x, y| {
                             x.extend(y.iter());
-                            x
+                            Ok(x)
                         },
-                    ),
+                    )?,
             );
         }
 
-        Self { set, k }
+        Ok(Self { set, k })
+    }
+
+    /// Write solid kmer set in sorted kmers format, it can be memory mapped see set::Mmap
+    pub fn write_mmap<W>(&self, writer: W) -> error::Result<()>
+    where
+        W: std::io::Write,
+    {
+        set::Mmap::write(writer, self.k, self.set.iter().copied())
     }
 }
 
-impl set::KmerSet for Hash {
-    fn get(&self, kmer: u64) -> bool {
-        self.set.contains(&cocktail::kmer::canonical(kmer, self.k))
+impl<K> set::KmerSet<K> for Hash<K>
+where
+    K: kmer::Kmer,
+{
+    fn get(&self, kmer: K) -> bool {
+        self.set.contains(&kmer.canonical(self.k))
     }
 
     fn k(&self) -> u8 {
@@ -186,13 +220,15 @@ impl set::KmerSet for Hash {
 mod tests {
     use super::*;
 
+    use crate::kmer::Kmer as _;
+
     static FILE: &[u8] = b">1\nACGTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTGACGACTGTAAAGCGAGTGGCCGTGGAATTTCAAGCTCAATTAGCCGAACCAATCCGCCTA";
 
     #[test]
     fn canonical() {
         let file = std::io::Cursor::new(FILE);
 
-        let hash = Hash::from_fasta(file, 11);
+        let hash: Hash = Hash::from_fasta(file, "kmers.fasta".to_string(), 11).unwrap();
 
         let set: crate::set::BoxKmerSet = Box::new(hash);
 
@@ -207,7 +243,7 @@ mod tests {
     fn forward() {
         let file = std::io::Cursor::new(FILE);
 
-        let hash = Hash::from_fasta(file, 11);
+        let hash: Hash = Hash::from_fasta(file, "kmers.fasta".to_string(), 11).unwrap();
 
         let set: crate::set::BoxKmerSet = Box::new(hash);
 
@@ -222,21 +258,52 @@ mod tests {
     fn absence() {
         let file = std::io::Cursor::new(FILE);
 
-        let hash = Hash::from_fasta(file, 11);
+        let hash: Hash = Hash::from_fasta(file, "kmers.fasta".to_string(), 11).unwrap();
 
         let set: crate::set::BoxKmerSet = Box::new(hash);
 
         assert!(!set.get(0));
     }
 
+    #[test]
+    fn invalid() {
+        let file = std::io::Cursor::new(b"ACGT\n>1\nACGTACGTACGT\n".to_vec());
+
+        let result = Hash::<u64>::from_fasta(file, "kmers.fasta".to_string(), 11);
+
+        assert!(result
+            .err()
+            .unwrap()
+            .to_string()
+            .starts_with("Invalid record 0 of input kmers.fasta at byte 0"));
+    }
+
     #[test]
     fn k() {
         let file = std::io::Cursor::new(FILE);
 
-        let hash = Hash::from_fasta(file, 11);
+        let hash: Hash = Hash::from_fasta(file, "kmers.fasta".to_string(), 11).unwrap();
 
         let set: crate::set::BoxKmerSet = Box::new(hash);
 
         assert_eq!(set.k(), 11);
     }
+
+    #[test]
+    fn write_mmap() -> error::Result<()> {
+        let hash =
+            Hash::<u128>::from_fasta(std::io::Cursor::new(FILE), "kmers.fasta".to_string(), 41)?;
+
+        let file = tempfile::NamedTempFile::new()?;
+        hash.write_mmap(file.reopen()?)?;
+        let mmap = set::Mmap::<u128>::open(file.path(), Some(41))?;
+
+        assert_eq!(mmap.len(), hash.set.len());
+        for kmer in hash.set.iter() {
+            assert!(set::KmerSet::get(&mmap, *kmer));
+            assert!(set::KmerSet::get(&mmap, kmer.revcomp(41)));
+        }
+
+        Ok(())
+    }
 }
@@ -0,0 +1,370 @@
+//! Memory mapped sets, solid kmers are read lazily from an on disk file share in page cache
+
+/* std use */
+
+/* crates use */
+
+/* project use */
+use crate::error;
+use crate::kmer;
+use crate::kmer::Kmer as _;
+use crate::set;
+
+/// Magic number at begin of sorted kmers file
+const MAGIC: &[u8; 8] = b"BRKMERS1";
+
+/// Length of header, magic number, kmer size and padding
+const HEADER_LEN: usize = 16;
+
+/// Solid kmer set store in a file of sorted canonical kmers, file is memory mapped
+///
+/// Kmers are store in little endian on 8 bytes if k is lower or equal to 32 and on 16 bytes otherwise, lookup is a binary search
+pub struct Mmap<K = u64> {
+    map: memmap2::Mmap,
+    k: u8,
+    width: usize,
+    len: usize,
+    kmer: std::marker::PhantomData<K>,
+}
+
+impl<K> Mmap<K>
+where
+    K: kmer::Kmer,
+{
+    /// Map a sorted kmers file write by Mmap::write, kmer size is read in file and must match k if it's set
+    pub fn open<P>(path: P, k: Option<u8>) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(path)?;
+
+        // safety: br never write in a solid file it read, file must not be truncate by another process
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+
+        if map.len() < HEADER_LEN || &map[..MAGIC.len()] != MAGIC || map[MAGIC.len()] == 0 {
+            return Err(error::Error::InvalidMmapSolid.into());
+        }
+
+        let found = map[MAGIC.len()];
+        if found > K::MAX_K {
+            return Err(error::Error::KmerSizeTooLarge(found.into()).into());
+        }
+        if let Some(expected) = k.filter(|expected| *expected != found) {
+            return Err(error::Error::MmapKmerSizeMismatch { expected, found }.into());
+        }
+
+        let width = kmer_width(found);
+        if (map.len() - HEADER_LEN) % width != 0 {
+            return Err(error::Error::InvalidMmapSolid.into());
+        }
+
+        // lookup are random, kernel readahead is useless
+        #[cfg(unix)]
+        map.advise(memmap2::Advice::Random)?;
+
+        Ok(Self {
+            len: (map.len() - HEADER_LEN) / width,
+            map,
+            k: found,
+            width,
+            kmer: std::marker::PhantomData,
+        })
+    }
+
+    /// Write sorted kmers file, kmers are canonicalize, sort and deduplicate, memory usage is proportional to number of kmers
+    pub fn write<W, I>(mut writer: W, k: u8, kmers: I) -> error::Result<()>
+    where
+        W: std::io::Write,
+        I: std::iter::IntoIterator<Item = K>,
+    {
+        if k == 0 {
+            return Err(error::Error::KmerSizeNull.into());
+        } else if k > K::MAX_K {
+            return Err(error::Error::KmerSizeTooLarge(k.into()).into());
+        }
+
+        let mut kmers: Vec<K> = kmers.into_iter().map(|kmer| kmer.canonical(k)).collect();
+        kmers.sort_unstable();
+        kmers.dedup();
+
+        let width = kmer_width(k);
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[k])?;
+        writer.write_all(&[0; HEADER_LEN - MAGIC.len() - 1])?;
+        for kmer in kmers {
+            writer.write_all(&kmer.to_u128().to_le_bytes()[..width])?;
+        }
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Number of canonical kmer in set
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return true if set didn't contains any kmer
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn kmer(&self, index: usize) -> K {
+        let begin = HEADER_LEN + index * self.width;
+
+        let mut bytes = [0; 16];
+        bytes[..self.width].copy_from_slice(&self.map[begin..begin + self.width]);
+
+        K::from_u128(u128::from_le_bytes(bytes))
+    }
+}
+
+/// Number of bytes use to store a kmer of size k in sorted kmers file
+fn kmer_width(k: u8) -> usize {
+    if k <= <u64 as kmer::Kmer>::MAX_K {
+        8
+    } else {
+        16
+    }
+}
+
+impl<K> set::KmerSet<K> for Mmap<K>
+where
+    K: kmer::Kmer,
+{
+    fn get(&self, kmer: K) -> bool {
+        let canonical = kmer.canonical(self.k);
+
+        // binary search on mapped kmers
+        let (mut low, mut high) = (0, self.len);
+        while low < high {
+            let mid = low + (high - low) / 2;
+
+            match self.kmer(mid).cmp(&canonical) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return true,
+            }
+        }
+
+        false
+    }
+
+    fn k(&self) -> u8 {
+        self.k
+    }
+}
+
+/// Solid kmer set store in an uncompressed pcon solid file, file is memory mapped
+///
+/// File is the kmer size on one byte followed by pcon bitfield of 2^(2k-1) bits, use it only for small k
+///
+/// Bit of a kmer is at index canonical >> 1, canonical form is the one with an even number of set bits, so a pcon solid file can be convert with `gzip -dc`
+pub struct Bitfield {
+    map: memmap2::Mmap,
+    k: u8,
+}
+
+impl Bitfield {
+    /// Map an uncompressed pcon solid file
+    pub fn open<P>(path: P) -> error::Result<Self>
+    where
+        P: std::convert::AsRef<std::path::Path>,
+    {
+        let file = std::fs::File::open(path)?;
+
+        // safety: br never write in a solid file it read, file must not be truncate by another process
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+
+        let k = map.first().copied().unwrap_or(0);
+        let valid = k > 0
+            && k <= <u64 as kmer::Kmer>::MAX_K
+            && (map.len() - 1) as u64 == Self::bitfield_len(k);
+
+        if !valid && map.starts_with(&[0x1f, 0x8b]) {
+            return Err(error::Error::CompressedMmapSolid.into());
+        } else if !valid {
+            return Err(error::Error::InvalidBitfieldSolid.into());
+        }
+
+        // lookup are random, kernel readahead is useless
+        #[cfg(unix)]
+        map.advise(memmap2::Advice::Random)?;
+
+        Ok(Self { map, k })
+    }
+
+    /// Number of bytes of pcon bitfield for kmer size k
+    pub fn bitfield_len(k: u8) -> u64 {
+        (1u64 << (2 * k as u64 - 1)).div_ceil(8)
+    }
+}
+
+impl set::KmerSet for Bitfield {
+    fn get(&self, kmer: u64) -> bool {
+        let canonical = if kmer.count_ones() % 2 == 0 {
+            kmer
+        } else {
+            kmer.revcomp(self.k)
+        };
+        let index = canonical >> 1;
+
+        self.map[1 + (index / 8) as usize] & (1 << (index % 8)) != 0
+    }
+
+    fn k(&self) -> u8 {
+        self.k
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Read as _;
+
+    static SEQ: &[u8] = b"ACGTGGGAATTGTGGCCACATCACGAGGTCCTGCGTATTGACGACTGTAAAGCGAGTGGCCGTGGAATTTCAAGCTCAATTAGCCGAACCAATCCGCCTA";
+
+    #[test]
+    fn write_open() -> error::Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+
+        Mmap::write(
+            std::io::BufWriter::new(file.reopen()?),
+            11,
+            cocktail::tokenizer::Tokenizer::new(SEQ, 11),
+        )?;
+
+        let set: crate::set::BoxKmerSet = Box::new(Mmap::open(file.path(), Some(11))?);
+
+        assert_eq!(set.k(), 11);
+        for kmer in cocktail::tokenizer::Tokenizer::new(SEQ, 11) {
+            assert!(set.get(kmer));
+            assert!(set.get(kmer.revcomp(11)));
+        }
+        assert!(!set.get(cocktail::kmer::seq2bit(b"AAAAAAAAAAA")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_k() -> error::Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        Mmap::write(file.reopen()?, 31, kmer::Canonical::<u64>::new(SEQ, 31)?)?;
+
+        let set = Mmap::<u64>::open(file.path(), None)?;
+        assert_eq!(set::KmerSet::k(&set), 31);
+        assert_eq!(set.len(), SEQ.len() - 30);
+        for window in SEQ.windows(31) {
+            let kmer = u64::seq2bit(window);
+            assert!(set::KmerSet::get(&set, kmer));
+            assert!(set::KmerSet::get(&set, kmer.revcomp(31)));
+        }
+        assert!(!set::KmerSet::get(&set, u64::seq2bit(&[b'A'; 31])));
+
+        let file = tempfile::NamedTempFile::new()?;
+        Mmap::write(file.reopen()?, 41, kmer::Canonical::<u128>::new(SEQ, 41)?)?;
+
+        assert!(Mmap::<u64>::open(file.path(), None).is_err());
+        let set = Mmap::<u128>::open(file.path(), None)?;
+        assert_eq!(
+            std::fs::metadata(file.path())?.len(),
+            (HEADER_LEN + set.len() * 16) as u64
+        );
+        for window in SEQ.windows(41) {
+            let kmer = u128::seq2bit(window);
+            assert!(set::KmerSet::get(&set, kmer));
+            assert!(set::KmerSet::get(&set, kmer.revcomp(41)));
+        }
+        assert!(!set::KmerSet::get(&set, u128::seq2bit(&[b'A'; 41])));
+
+        Ok(())
+    }
+
+    #[test]
+    fn invalid() -> error::Result<()> {
+        let file = tempfile::NamedTempFile::new()?;
+        std::fs::write(file.path(), b"not a solid file")?;
+
+        assert!(Mmap::<u64>::open(file.path(), None).is_err());
+        assert!(Bitfield::open(file.path()).is_err());
+        assert!(Bitfield::open("tests/data/raw.k11.a2.solid").is_err());
+
+        let empty = tempfile::NamedTempFile::new()?;
+        Mmap::<u64>::write(empty.reopen()?, 5, Vec::new())?;
+
+        let set = Mmap::<u64>::open(empty.path(), None)?;
+        assert!(set.is_empty());
+        assert!(!set::KmerSet::get(&set, 0));
+
+        assert!(Mmap::<u64>::open(empty.path(), Some(7)).is_err());
+        assert!(Mmap::<u64>::write(Vec::new(), 33, Vec::new()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn bitfield() -> error::Result<()> {
+        let mut solid = pcon::solid::Solid::new(11);
+        for kmer in cocktail::tokenizer::Canonical::new(SEQ, 11) {
+            solid.set(kmer, true);
+        }
+
+        let file = tempfile::NamedTempFile::new()?;
+        set::Pcon::new(solid).write_mmap_bitfield(std::io::BufWriter::new(file.reopen()?))?;
+
+        let set: crate::set::BoxKmerSet = Box::new(Bitfield::open(file.path())?);
+
+        assert_eq!(set.k(), 11);
+        assert_eq!(
+            std::fs::metadata(file.path())?.len(),
+            1 + Bitfield::bitfield_len(11)
+        );
+        for kmer in cocktail::tokenizer::Tokenizer::new(SEQ, 11) {
+            assert!(set.get(kmer));
+            assert!(set.get(kmer.revcomp(11)));
+        }
+        assert!(!set.get(cocktail::kmer::seq2bit(b"AAAAAAAAAAA")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn pcon_solid() -> error::Result<()> {
+        let mut decompressed = Vec::new();
+        niffler::get_reader(Box::new(std::fs::File::open(
+            "tests/data/raw.k11.a2.solid",
+        )?))?
+        .0
+        .read_to_end(&mut decompressed)?;
+
+        let pcon = set::Pcon::from_pcon_solid(std::io::BufReader::new(std::fs::File::open(
+            "tests/data/raw.k11.a2.solid",
+        )?))?;
+
+        let mut written = Vec::new();
+        pcon.write_mmap_bitfield(&mut written)?;
+        assert_eq!(written, decompressed);
+
+        let file = tempfile::NamedTempFile::new()?;
+        std::fs::write(file.path(), &decompressed)?;
+        let bitfield = Bitfield::open(file.path())?;
+
+        let file = tempfile::NamedTempFile::new()?;
+        pcon.write_mmap(file.reopen()?)?;
+        let mmap = Mmap::<u64>::open(file.path(), Some(11))?;
+
+        for kmer in 0..(1 << 22) {
+            assert_eq!(
+                set::KmerSet::get(&bitfield, kmer),
+                set::KmerSet::get(&pcon, kmer)
+            );
+            assert_eq!(
+                set::KmerSet::get(&mmap, kmer),
+                set::KmerSet::get(&pcon, kmer)
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
 //! Pcon set
 
 /* std use */
+use std::io::Read as _;
 
 /* crates use */
 #[cfg(feature = "parallel")]
@@ -8,6 +9,7 @@ use rayon::prelude::*;
 
 /* project use */
 use crate::error;
+use crate::parse;
 use crate::set;
 
 pub struct Pcon {
@@ -45,16 +47,17 @@ impl Pcon {
     }
 
     #[cfg(not(feature = "parallel"))]
-    pub fn from_fasta<R>(input: R, k: u8) -> Self
+    pub fn from_fasta<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = pcon::solid::Solid::new(k);
 
+        let (input, offset) = parse::CountReader::new(input);
         let mut reader = noodles::fasta::Reader::new(input);
-        let mut records = reader.records();
 
-        while let Some(Ok(record)) = records.next() {
+        for record in parse::Records::new(reader.records(), name, offset, false) {
+            let record = record?;
             if record.sequence().len() >= k as usize {
                 let kmerizer = cocktail::tokenizer::Canonical::new(record.sequence().as_ref(), k);
 
@@ -64,25 +67,26 @@ impl Pcon {
             }
         }
 
-        Self { set }
+        Ok(Self { set })
     }
 
     #[cfg(feature = "parallel")]
-    pub fn from_fasta<R>(input: R, k: u8) -> Self
+    pub fn from_fasta<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = pcon::solid::Solid::new(k);
 
+        let (input, offset) = parse::CountReader::new(input);
         let mut reader = noodles::fasta::Reader::new(input);
 
-        let mut iter = reader.records();
+        let mut iter = parse::Records::new(reader.records(), name, offset, false);
         let mut records = Vec::with_capacity(8192);
 
         let mut end = true;
         while end {
             log::info!("Start populate buffer");
-            end = crate::populate_buffer(&mut iter, &mut records, 8192);
+            end = crate::populate_buffer(&mut iter, &mut records, 8192)?;
             log::info!("End populate buffer {}", records.len());
 
             set.extend(
@@ -108,21 +112,22 @@ impl Pcon {
             );
         }
 
-        Self { set }
+        Ok(Self { set })
     }
 
     #[cfg(feature = "fastq")]
     #[cfg(not(feature = "parallel"))]
-    pub fn from_fastq<R>(input: R, k: u8) -> Self
+    pub fn from_fastq<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = pcon::solid::Solid::new(k);
 
+        let (input, offset) = parse::CountReader::new(input);
         let mut reader = noodles::fastq::Reader::new(input);
-        let mut records = reader.records();
 
-        while let Some(Ok(record)) = records.next() {
+        for record in parse::Records::new(reader.records(), name, offset, false) {
+            let record = record?;
             if record.sequence().len() >= k as usize {
                 let kmerizer = cocktail::tokenizer::Canonical::new(record.sequence(), k);
 
@@ -132,26 +137,27 @@ impl Pcon {
             }
         }
 
-        Self { set }
+        Ok(Self { set })
     }
 
     #[cfg(feature = "fastq")]
     #[cfg(feature = "parallel")]
-    pub fn from_fastq<R>(input: R, k: u8) -> Self
+    pub fn from_fastq<R>(input: R, name: String, k: u8) -> error::Result<Self>
     where
         R: std::io::BufRead,
     {
         let mut set = pcon::solid::Solid::new(k);
 
+        let (input, offset) = parse::CountReader::new(input);
         let mut reader = noodles::fastq::Reader::new(input);
 
-        let mut iter = reader.records();
+        let mut iter = parse::Records::new(reader.records(), name, offset, false);
         let mut records = Vec::with_capacity(8192);
 
         let mut end = true;
         while end {
             log::info!("Start populate buffer");
-            end = crate::populate_bufferq(&mut iter, &mut records, 8192);
+            end = crate::populate_buffer(&mut iter, &mut records, 8192)?;
             log::info!("End populate buffer {}", records.len());
 
             set.extend(
@@ -177,12 +183,123 @@ impl Pcon {
             );
         }
 
-        Self { set }
+        Ok(Self { set })
+    }
+
+    #[cfg(feature = "kff")]
+    pub fn from_kff<R>(input: R, k: Option<u8>) -> error::Result<Self>
+    where
+        R: std::io::Read,
+    {
+        let (reader, k) = set::open_kff(input, k)?;
+        if k > <u64 as crate::kmer::Kmer>::MAX_K {
+            return Err(error::Error::KmerSizeTooLarge(k.into()).into());
+        }
+
+        let mut set = pcon::solid::Solid::new(k);
+        set::kff_kmers(reader, k, |seq| {
+            set.set(cocktail::kmer::seq2bit(seq), true);
+        })?;
+
+        Ok(Self { set })
     }
 
     pub fn new(set: pcon::solid::Solid) -> Self {
         Self { set }
     }
+
+    /// Write solid kmer set in pcon solid format
+    pub fn write_solid<W>(&self, writer: W) -> error::Result<()>
+    where
+        W: std::io::Write,
+    {
+        self.set.serialize(writer)?;
+
+        Ok(())
+    }
+
+    /// Write solid kmer set in sorted kmers format, it can be memory mapped see set::Mmap
+    pub fn write_mmap<W>(&self, writer: W) -> error::Result<()>
+    where
+        W: std::io::Write,
+    {
+        set::Mmap::write(writer, self.set.k(), self.bitfield_kmers()?)
+    }
+
+    /// Write solid kmer set in uncompressed pcon solid format, it can be memory mapped see set::Bitfield
+    pub fn write_mmap_bitfield<W>(&self, mut writer: W) -> error::Result<()>
+    where
+        W: std::io::Write,
+    {
+        std::io::copy(&mut self.uncompressed()?, &mut writer)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+
+    /// Write each canonical solid kmer as a fasta record
+    pub fn write_fasta<W>(&self, mut writer: W) -> error::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let k = self.set.k();
+
+        for (id, kmer) in self.canonicals().enumerate() {
+            writeln!(writer, ">{}\n{}", id, cocktail::kmer::kmer2seq(kmer, k))?;
+        }
+
+        Ok(())
+    }
+
+    /// Get uncompressed pcon solid format, kmer size on one byte followed by bitfield
+    fn uncompressed(&self) -> error::Result<Box<dyn std::io::Read>> {
+        let mut compressed = Vec::new();
+        self.set.serialize(&mut compressed)?;
+
+        Ok(niffler::get_reader(Box::new(std::io::Cursor::new(compressed)))?.0)
+    }
+
+    /// Get canonical solid kmers, bitfield is read by byte and only set bits are visited
+    fn bitfield_kmers(&self) -> error::Result<Vec<u64>> {
+        let mut reader = self.uncompressed()?;
+        reader.read_exact(&mut [0; 1])?;
+
+        let mut kmers = Vec::new();
+        let mut buffer = vec![0u8; 1 << 16];
+        let mut first = 0;
+        loop {
+            let len = reader.read(&mut buffer)?;
+            if len == 0 {
+                break;
+            }
+
+            for (offset, byte) in buffer[..len].iter().enumerate() {
+                let mut bits = *byte;
+                while bits != 0 {
+                    let index = (first + offset as u64) * 8 + bits.trailing_zeros() as u64;
+                    kmers.push(index2kmer(index));
+                    bits &= bits - 1;
+                }
+            }
+            first += len as u64;
+        }
+
+        Ok(kmers)
+    }
+
+    /// Iterate over canonical solid kmers, pcon store only kmers with an even number of set bits at index kmer >> 1, so only half of kmer space is scan and canonical form is never compute
+    fn canonicals(&self) -> impl Iterator<Item = u64> + '_ {
+        let k = self.set.k() as u64;
+
+        (0..(1u64 << (2 * k - 1)))
+            .map(index2kmer)
+            .filter(|kmer| self.set.get(*kmer))
+    }
+}
+
+/// Get canonical kmer store at an index of pcon bitfield
+fn index2kmer(index: u64) -> u64 {
+    (index << 1) | (index.count_ones() as u64 & 1)
 }
 
 impl set::KmerSet for Pcon {
@@ -241,6 +358,37 @@ mod tests {
         assert!(!set.get(0));
     }
 
+    #[test]
+    fn write() {
+        let mut solid = pcon::solid::Solid::new(11);
+        for cano in cocktail::tokenizer::Canonical::new(SEQ, 11) {
+            solid.set(cano, true);
+        }
+        let set = Pcon::new(solid);
+
+        let mut output = Vec::new();
+        set.write_solid(&mut output).unwrap();
+        let reload: crate::set::BoxKmerSet =
+            Box::new(Pcon::from_pcon_solid(output.as_slice()).unwrap());
+        for kmer in cocktail::tokenizer::Tokenizer::new(SEQ, 11) {
+            assert!(reload.get(kmer))
+        }
+
+        let mut output = Vec::new();
+        set.write_fasta(&mut output).unwrap();
+        let reload: crate::set::BoxKmerSet =
+            Box::new(Pcon::from_fasta(output.as_slice(), "solid.fasta".to_string(), 11).unwrap());
+        for kmer in cocktail::tokenizer::Tokenizer::new(SEQ, 11) {
+            assert!(reload.get(kmer))
+        }
+        assert_eq!(
+            output.iter().filter(|c| **c == b'>').count(),
+            cocktail::tokenizer::Canonical::new(SEQ, 11)
+                .collect::<rustc_hash::FxHashSet<u64>>()
+                .len()
+        );
+    }
+
     #[test]
     fn k() {
         let mut solid = pcon::solid::Solid::new(11);
@@ -0,0 +1,69 @@
+//! Kmer abundance spectrum
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+
+/// Number of kmer for each abundance
+pub fn histogram(counts: &[pcon::CountTypeNoAtomic]) -> Vec<u64> {
+    let mut histogram = Vec::new();
+
+    for count in counts {
+        let abundance = *count as usize;
+
+        if abundance >= histogram.len() {
+            histogram.resize(abundance + 1, 0);
+        }
+
+        histogram[abundance] += 1;
+    }
+
+    histogram
+}
+
+/// Write spectrum in csv, threshold and method use to select it are repeat on each row
+pub fn write_csv<W>(
+    mut writer: W,
+    histogram: &[u64],
+    threshold: pcon::CountTypeNoAtomic,
+    method: &str,
+) -> std::io::Result<()>
+where
+    W: std::io::Write,
+{
+    writeln!(writer, "abundance,count,threshold,method")?;
+
+    for (abundance, count) in histogram.iter().enumerate() {
+        writeln!(writer, "{},{},{},{}", abundance, count, threshold, method)?;
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv() {
+        let counts: Vec<pcon::CountTypeNoAtomic> = vec![0, 1, 1, 3, 0, 1];
+
+        let histogram = histogram(&counts);
+        assert_eq!(histogram, vec![2, 3, 0, 1]);
+
+        let mut output = Vec::new();
+        write_csv(&mut output, &histogram, 2, "first-minimum").unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "abundance,count,threshold,method
+0,2,2,first-minimum
+1,3,2,first-minimum
+2,0,2,first-minimum
+3,1,2,first-minimum
+"
+        );
+    }
+}
@@ -0,0 +1,311 @@
+//! Correction statistics
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::correct;
+use crate::report;
+
+/// Counter of one correction method
+#[derive(std::fmt::Debug, std::default::Default, std::clone::Clone, std::cmp::PartialEq)]
+pub struct MethodStats {
+    /// Number of weak kmer stretch method try to correct
+    pub attempts: u64,
+
+    /// Number of weak kmer stretch corrected
+    pub successes: u64,
+
+    /// Number of failure by reason
+    pub failures: std::collections::BTreeMap<&'static str, u64>,
+}
+
+impl MethodStats {
+    /// Add counter of other in self
+    pub fn merge(&mut self, other: Self) {
+        self.attempts += other.attempts;
+        self.successes += other.successes;
+
+        for (reason, count) in other.failures {
+            *self.failures.entry(reason).or_insert(0) += count;
+        }
+    }
+}
+
+/// Counter of a correction run
+#[derive(std::fmt::Debug, std::default::Default, std::clone::Clone, std::cmp::PartialEq)]
+pub struct Stats {
+    /// Number of read process
+    pub reads: u64,
+
+    /// Number of read with at least one edit
+    pub corrected_reads: u64,
+
+    /// Number of read drop by trimming because it didn't contains any solid kmer
+    pub trimmed_reads: u64,
+
+    /// Number of base in input reads
+    pub bases: u64,
+
+    /// Number of base inserted
+    pub inserted: u64,
+
+    /// Number of base deleted
+    pub deleted: u64,
+
+    /// Number of base substituted
+    pub substituted: u64,
+
+    /// Counter of each method
+    pub methods: std::collections::BTreeMap<&'static str, MethodStats>,
+}
+
+impl Stats {
+    /// Add result of one read correction
+    pub fn add_read(&mut self, length: usize, edits: &[correct::Edit], events: &[correct::Event]) {
+        self.reads += 1;
+        self.bases += length as u64;
+
+        if !edits.is_empty() {
+            self.corrected_reads += 1;
+        }
+
+        for edit in edits {
+            self.substituted += edit
+                .original
+                .iter()
+                .zip(edit.replacement.iter())
+                .filter(|(a, b)| a != b)
+                .count() as u64;
+            self.inserted += edit.replacement.len().saturating_sub(edit.original.len()) as u64;
+            self.deleted += edit.original.len().saturating_sub(edit.replacement.len()) as u64;
+        }
+
+        for event in events {
+            let method = self.methods.entry(event.method).or_default();
+
+            method.attempts += 1;
+            if event.outcome.is_corrected() {
+                method.successes += 1;
+            } else {
+                *method.failures.entry(event.outcome.name()).or_insert(0) += 1;
+            }
+        }
+    }
+
+    /// Add counter of other in self
+    pub fn merge(&mut self, other: Self) {
+        self.reads += other.reads;
+        self.corrected_reads += other.corrected_reads;
+        self.trimmed_reads += other.trimmed_reads;
+        self.bases += other.bases;
+        self.inserted += other.inserted;
+        self.deleted += other.deleted;
+        self.substituted += other.substituted;
+
+        for (name, method) in other.methods {
+            self.methods.entry(name).or_default().merge(method);
+        }
+    }
+
+    /// Write a human readable summary
+    pub fn write_summary<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        writeln!(
+            writer,
+            "reads: {} corrected: {} trimmed: {} bases: {} substituted: {} inserted: {} deleted: {}",
+            self.reads,
+            self.corrected_reads,
+            self.trimmed_reads,
+            self.bases,
+            self.substituted,
+            self.inserted,
+            self.deleted
+        )?;
+
+        for (name, method) in self.methods.iter() {
+            writeln!(
+                writer,
+                "method {}: attempts {} successes {} failures {:?}",
+                name, method.attempts, method.successes, method.failures
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Write statistics in json
+    pub fn write_json<W>(&self, mut writer: W) -> std::io::Result<()>
+    where
+        W: std::io::Write,
+    {
+        let methods = self
+            .methods
+            .iter()
+            .map(|(name, method)| {
+                let failures = method
+                    .failures
+                    .iter()
+                    .map(|(reason, count)| format!("\"{}\":{}", report::json_escape(reason), count))
+                    .collect::<Vec<String>>()
+                    .join(",");
+
+                format!(
+                    "\"{}\":{{\"attempts\":{},\"successes\":{},\"failures\":{{{}}}}}",
+                    report::json_escape(name),
+                    method.attempts,
+                    method.successes,
+                    failures
+                )
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+
+        writeln!(
+            writer,
+            "{{\"reads\":{},\"corrected_reads\":{},\"trimmed_reads\":{},\"bases\":{},\"substituted\":{},\"inserted\":{},\"deleted\":{},\"methods\":{{{}}}}}",
+            self.reads,
+            self.corrected_reads,
+            self.trimmed_reads,
+            self.bases,
+            self.substituted,
+            self.inserted,
+            self.deleted,
+            methods
+        )?;
+
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read() -> (Vec<correct::Edit>, Vec<correct::Event>) {
+        let edits = vec![
+            correct::Edit {
+                pos: 5,
+                original: b"T".to_vec(),
+                replacement: b"C".to_vec(),
+                method: "One",
+                scenario: Some("ScenarioOne::S".to_string()),
+            },
+            correct::Edit {
+                pos: 10,
+                original: b"".to_vec(),
+                replacement: b"GA".to_vec(),
+                method: "Graph",
+                scenario: None,
+            },
+            correct::Edit {
+                pos: 20,
+                original: b"AT".to_vec(),
+                replacement: b"A".to_vec(),
+                method: "Graph",
+                scenario: None,
+            },
+        ];
+
+        let events = vec![
+            correct::Event {
+                pos: 5,
+                method: "One",
+                outcome: correct::Outcome::Corrected(correct::Correction::new(
+                    b"C".to_vec(),
+                    1,
+                    Some("ScenarioOne::S".to_string()),
+                )),
+            },
+            correct::Event {
+                pos: 8,
+                method: "One",
+                outcome: correct::Outcome::NoScenario,
+            },
+            correct::Event {
+                pos: 30,
+                method: "Graph",
+                outcome: correct::Outcome::Branching,
+            },
+        ];
+
+        (edits, events)
+    }
+
+    #[test]
+    fn add_read() {
+        let (edits, events) = read();
+        let mut stats = Stats::default();
+
+        stats.add_read(40, &edits, &events);
+        stats.add_read(40, &[], &[]);
+
+        assert_eq!(stats.reads, 2);
+        assert_eq!(stats.corrected_reads, 1);
+        assert_eq!(stats.bases, 80);
+        assert_eq!(stats.substituted, 1);
+        assert_eq!(stats.inserted, 2);
+        assert_eq!(stats.deleted, 1);
+
+        assert_eq!(stats.methods["One"].attempts, 2);
+        assert_eq!(stats.methods["One"].successes, 1);
+        assert_eq!(stats.methods["One"].failures["NoScenario"], 1);
+        assert_eq!(stats.methods["Graph"].attempts, 1);
+        assert_eq!(stats.methods["Graph"].successes, 0);
+        assert_eq!(stats.methods["Graph"].failures["Branching"], 1);
+    }
+
+    #[test]
+    fn merge() {
+        let (edits, events) = read();
+
+        let mut all = Stats::default();
+        all.add_read(40, &edits, &events);
+        all.add_read(40, &edits, &events);
+
+        let mut first = Stats::default();
+        first.add_read(40, &edits, &events);
+        let mut second = Stats::default();
+        second.add_read(40, &edits, &events);
+        first.merge(second);
+
+        assert_eq!(all, first);
+    }
+
+    #[test]
+    fn summary() {
+        let (edits, events) = read();
+        let mut stats = Stats::default();
+        stats.add_read(40, &edits, &events);
+
+        let mut output = Vec::new();
+        stats.write_summary(&mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "reads: 1 corrected: 1 trimmed: 0 bases: 40 substituted: 1 inserted: 2 deleted: 1
+method Graph: attempts 1 successes 0 failures {\"Branching\": 1}
+method One: attempts 2 successes 1 failures {\"NoScenario\": 1}
+"
+        );
+    }
+
+    #[test]
+    fn json() {
+        let (edits, events) = read();
+        let mut stats = Stats::default();
+        stats.add_read(40, &edits, &events);
+
+        let mut output = Vec::new();
+        stats.write_json(&mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"reads\":1,\"corrected_reads\":1,\"trimmed_reads\":0,\"bases\":40,\"substituted\":1,\"inserted\":2,\"deleted\":1,\"methods\":{\"Graph\":{\"attempts\":1,\"successes\":0,\"failures\":{\"Branching\":1}},\"One\":{\"attempts\":2,\"successes\":1,\"failures\":{\"NoScenario\":1}}}}
+"
+        );
+    }
+}
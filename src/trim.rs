@@ -0,0 +1,131 @@
+//! Trimming and splitting of read region not supported by solid kmer
+
+/* std use */
+
+/* crate use */
+
+/* project use */
+use crate::correct;
+use crate::kmer;
+use crate::set;
+
+/// Post correction trimming parameter
+#[derive(std::fmt::Debug, std::clone::Clone, std::marker::Copy, std::cmp::PartialEq)]
+pub struct Trim {
+    /// Split read at internal weak region longer than this value, no split if None
+    pub split: Option<usize>,
+}
+
+impl Trim {
+    /// Create a new trimming parameter
+    pub fn new(split: Option<usize>) -> Self {
+        Self { split }
+    }
+
+    /// Get region of read to keep, empty if read didn't contains any solid kmer
+    pub fn regions<K>(
+        &self,
+        seq: &[u8],
+        valid_kmer: &set::BoxKmerSet<K>,
+    ) -> Vec<std::ops::Range<usize>>
+    where
+        K: kmer::Kmer,
+    {
+        let solids = solid_regions(seq, valid_kmer);
+
+        match self.split {
+            None => match (solids.first(), solids.last()) {
+                (Some(first), Some(last)) => vec![first.start..last.end],
+                _ => Vec::new(),
+            },
+            Some(gap) => {
+                let mut regions: Vec<std::ops::Range<usize>> = Vec::new();
+
+                for solid in solids {
+                    match regions.last_mut() {
+                        Some(last) if solid.start - last.end <= gap => last.end = solid.end,
+                        _ => regions.push(solid),
+                    }
+                }
+
+                regions
+            }
+        }
+    }
+}
+
+/// Get region of sequence covered by at least one solid kmer, kmer with an ambiguous base is never solid
+pub fn solid_regions<K>(seq: &[u8], valid_kmer: &set::BoxKmerSet<K>) -> Vec<std::ops::Range<usize>>
+where
+    K: kmer::Kmer,
+{
+    let k = valid_kmer.k();
+    let mut regions: Vec<std::ops::Range<usize>> = Vec::new();
+
+    let mut kmer = K::from_nuc(0);
+    // number of next kmer, current included, contains an ambiguous base
+    let mut ambiguous = k as usize;
+
+    for (i, nuc) in seq.iter().enumerate() {
+        kmer = correct::add_nuc_to_end(kmer, cocktail::kmer::nuc2bit(*nuc), k);
+        ambiguous = if correct::is_acgt(*nuc) {
+            ambiguous.saturating_sub(1)
+        } else {
+            k as usize
+        };
+
+        if i + 1 >= k as usize && ambiguous == 0 && valid_kmer.get(kmer) {
+            let begin = i + 1 - k as usize;
+
+            match regions.last_mut() {
+                Some(last) if last.end >= begin => last.end = i + 1,
+                _ => regions.push(begin..i + 1),
+            }
+        }
+    }
+
+    regions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set() -> set::BoxKmerSet<'static> {
+        let mut data = pcon::solid::Solid::new(5);
+        for kmer in cocktail::tokenizer::Tokenizer::new(b"ACGTGGGAATTGTGGCCACATCACGAGG", 5) {
+            data.set(kmer, true);
+        }
+
+        Box::new(set::Pcon::new(data))
+    }
+
+    #[test]
+    fn solid() {
+        let set = set();
+
+        assert_eq!(
+            solid_regions(b"TTTTTTACGTGGGAATTTTTTTTTTCCACATCACTTTTTT", &set),
+            vec![6..17, 25..34]
+        );
+        assert_eq!(solid_regions(b"ACGTGNGGAAT", &set), vec![0..5, 6..11]);
+        assert_eq!(
+            solid_regions(b"ACG", &set),
+            Vec::<std::ops::Range<usize>>::new()
+        );
+    }
+
+    #[test]
+    fn trim_split() {
+        let set = set();
+        let read = b"TTTTTTACGTGGGAATTTTTTTTTTCCACATCACTTTTTT";
+
+        assert_eq!(Trim::new(None).regions(read, &set), vec![6..34]);
+        assert_eq!(Trim::new(Some(8)).regions(read, &set), vec![6..34]);
+        assert_eq!(Trim::new(Some(7)).regions(read, &set), vec![6..17, 25..34]);
+        assert_eq!(
+            Trim::new(None).regions(b"TTTTTTTT", &set),
+            Vec::<std::ops::Range<usize>>::new()
+        );
+    }
+}
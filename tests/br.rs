@@ -5,6 +5,20 @@
 #[cfg(test)]
 mod tests {
 
+    /// Check command succeed and write only correction summary on stderr
+    fn only_summary(assert: assert_cmd::assert::Assert) {
+        let stderr = String::from_utf8_lossy(&assert.success().get_output().stderr).to_string();
+
+        assert!(stderr.starts_with("reads: "), "{}", stderr);
+        assert!(
+            stderr
+                .lines()
+                .all(|line| line.starts_with("reads: ") || line.starts_with("method ")),
+            "{}",
+            stderr
+        );
+    }
+
     #[test]
     fn fasta_count() -> std::io::Result<()> {
         let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
@@ -27,7 +41,310 @@ mod tests {
 
         let assert = cmd.assert();
 
-        assert.success().stderr(b"" as &[u8]);
+        only_summary(assert);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_rounds() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.rounds.fasta",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "-r",
+            "2",
+            "-K",
+            "11,13",
+            "first-minimum",
+        ]);
+
+        let assert = cmd.assert();
+
+        only_summary(assert);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_multi_k() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.multi_k.fasta",
+            "-c",
+            "gap-size",
+            "-c",
+            "greedy",
+            "-m",
+            "greedy=15",
+            "-P",
+            "greedy:max_search=12,confirm=4",
+            "-P",
+            "gap-size:confirm=3",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "-e",
+            "15",
+            "first-minimum",
+        ]);
+
+        let assert = cmd.assert();
+
+        only_summary(assert);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_trim_split() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.split.fasta",
+            "-g",
+            "20",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "first-minimum",
+        ]);
+
+        let assert = cmd.assert();
+
+        only_summary(assert);
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_solid_ratio() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.ratio.fasta",
+            "--annotate-ratio",
+            "--min-solid-ratio",
+            "0.5",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "first-minimum",
+        ]);
+
+        let assert = cmd.assert();
+
+        only_summary(assert);
+
+        let output = std::fs::read_to_string("tests/data/corr.ratio.fasta")?;
+        for line in output.lines().filter(|line| line.starts_with('>')) {
+            assert!(line.contains(" solid_before="));
+            assert!(line.contains(" solid_after="));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_config() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-F",
+            "tests/data/config.toml",
+            "-D",
+            "tests/data/config.dump.toml",
+            "-C",
+            "3",
+        ]);
+
+        let assert = cmd.assert();
+
+        only_summary(assert);
+
+        let dump = std::fs::read_to_string("tests/data/config.dump.toml")?;
+        assert!(dump.contains("confirm = 3"));
+        assert!(dump.contains("kmer-size = 11"));
+        assert!(dump.contains("max-search = 7"));
+        assert!(dump.contains("record-buffer = 8192"));
+        assert!(dump.contains("insertion-quality = 20"));
+        assert!(dump.contains("ambiguous = \"resolve\""));
+        assert!(dump.contains("compression-level = 6"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_save_solid() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.fasta",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "-s",
+            "tests/data/save.k11.solid",
+            "first-minimum",
+        ]);
+
+        only_summary(cmd.assert());
+
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.fasta",
+            "solid",
+            "-i",
+            "tests/data/save.k11.solid",
+            "-f",
+            "solid",
+        ]);
+
+        only_summary(cmd.assert());
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_save_mmap() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.count.fasta",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "-s",
+            "tests/data/save.k11.mmap",
+            "-f",
+            "mmap",
+            "first-minimum",
+        ]);
+
+        only_summary(cmd.assert());
+
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.mmap.fasta",
+            "solid",
+            "-i",
+            "tests/data/save.k11.mmap",
+            "-f",
+            "mmap",
+        ]);
+
+        only_summary(cmd.assert());
+
+        assert_eq!(
+            std::fs::read("tests/data/corr.mmap.fasta")?,
+            std::fs::read("tests/data/corr.count.fasta")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_save_mmap_bitfield() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.count.bitfield.fasta",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "-s",
+            "tests/data/save.k11.bitfield",
+            "-f",
+            "mmap-bitfield",
+            "first-minimum",
+        ]);
+
+        only_summary(cmd.assert());
+
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.bitfield.fasta",
+            "solid",
+            "-i",
+            "tests/data/save.k11.bitfield",
+            "-f",
+            "mmap-bitfield",
+        ]);
+
+        only_summary(cmd.assert());
+
+        assert_eq!(
+            std::fs::read("tests/data/corr.bitfield.fasta")?,
+            std::fs::read("tests/data/corr.count.bitfield.fasta")?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn fasta_spectrum() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.fasta",
+            "fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-k",
+            "11",
+            "-p",
+            "tests/data/spectrum.k11.csv",
+            "first-minimum",
+        ]);
+
+        only_summary(cmd.assert());
+
+        let spectrum = std::fs::read_to_string("tests/data/spectrum.k11.csv")?;
+        let mut lines = spectrum.lines();
+        assert_eq!(lines.next(), Some("abundance,count,threshold,method"));
+        assert!(lines.all(|line| line.ends_with(",first-minimum")));
 
         Ok(())
     }
@@ -53,7 +370,65 @@ mod tests {
 
         let assert = cmd.assert();
 
-        assert.success().stderr(b"" as &[u8]);
+        only_summary(assert);
+
+        Ok(())
+    }
+
+    #[test]
+    fn solid_multi_k() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.solid_multi_k.fasta",
+            "-c",
+            "gap-size",
+            "-c",
+            "greedy",
+            "-m",
+            "greedy=16",
+            "solid",
+            "-i",
+            "tests/data/raw.fasta",
+            "-i",
+            "tests/data/raw.fasta",
+            "-f",
+            "fasta",
+            "-k",
+            "11,15",
+        ]);
+
+        only_summary(cmd.assert());
+
+        Ok(())
+    }
+
+    #[test]
+    fn solid_invalid() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.fasta",
+            "solid",
+            "-i",
+            "tests/data/raw.fastq",
+            "-f",
+            "fasta",
+            "-k",
+            "11",
+        ]);
+
+        let stderr =
+            String::from_utf8_lossy(&cmd.assert().failure().get_output().stderr).to_string();
+        assert!(
+            stderr.contains("Invalid record 0 of input tests/data/raw.fastq at byte 0"),
+            "{}",
+            stderr
+        );
 
         Ok(())
     }
@@ -81,7 +456,136 @@ mod tests {
 
         let assert = cmd.assert();
 
-        assert.success().stderr(b"" as &[u8]);
+        only_summary(assert);
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_kmer_wide() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.fasta",
+            #[cfg(feature = "parallel")]
+            "-t",
+            #[cfg(feature = "parallel")]
+            "4",
+            "large-kmer",
+            "-i",
+            "tests/data/raw.fasta",
+            "-f",
+            "fasta",
+            "-k",
+            "41",
+        ]);
+
+        let assert = cmd.assert();
+
+        only_summary(assert);
+
+        Ok(())
+    }
+
+    #[test]
+    fn large_kmer_save_mmap() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.hash.k31.fasta",
+            "large-kmer",
+            "-i",
+            "tests/data/raw.fasta",
+            "-f",
+            "fasta",
+            "-k",
+            "31",
+            "-s",
+            "tests/data/save.k31.mmap",
+        ]);
+
+        only_summary(cmd.assert());
+
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.mmap.k31.fasta",
+            "large-kmer",
+            "-i",
+            "tests/data/save.k31.mmap",
+            "-f",
+            "mmap",
+            "-k",
+            "31",
+        ]);
+
+        only_summary(cmd.assert());
+
+        assert_eq!(
+            std::fs::read("tests/data/corr.mmap.k31.fasta")?,
+            std::fs::read("tests/data/corr.hash.k31.fasta")?
+        );
+
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fasta",
+            "-o",
+            "tests/data/corr.fasta",
+            "solid",
+            "-i",
+            "tests/data/save.k31.mmap",
+            "-f",
+            "mmap",
+            "-k",
+            "27",
+        ]);
+
+        cmd.assert().failure();
+
+        Ok(())
+    }
+
+    #[cfg(feature = "fastq")]
+    #[test]
+    fn fastq() -> std::io::Result<()> {
+        let mut cmd = assert_cmd::Command::cargo_bin("br").unwrap();
+        cmd.args(&[
+            "-i",
+            "tests/data/raw.fastq",
+            "-o",
+            "tests/data/corr.fastq",
+            #[cfg(feature = "parallel")]
+            "-t",
+            #[cfg(feature = "parallel")]
+            "4",
+            "solid",
+            "-i",
+            "tests/data/raw.k11.a2.solid",
+            "-f",
+            "solid",
+        ]);
+
+        let assert = cmd.assert();
+
+        only_summary(assert);
+
+        let mut reader = noodles::fastq::Reader::new(std::io::BufReader::new(std::fs::File::open(
+            "tests/data/corr.fastq",
+        )?));
+        let mut nb_record = 0;
+        for result in reader.records() {
+            let record = result?;
+            assert_eq!(record.sequence().len(), record.quality_scores().len());
+            nb_record += 1;
+        }
+        assert_eq!(nb_record, 20);
 
         Ok(())
     }